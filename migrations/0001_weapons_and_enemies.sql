@@ -0,0 +1,14 @@
+-- The tables as they were first made by hand.
+CREATE TABLE IF NOT EXISTS "weapons" (
+	"name"	TEXT NOT NULL UNIQUE,
+	"weight"	NUMERIC,
+	"value"	INTEGER,
+	"physique"	NUMERIC,
+	"technique"	NUMERIC,
+	"mystique"	NUMERIC,
+	PRIMARY KEY("name")
+);
+CREATE TABLE IF NOT EXISTS "enemies" (
+	"name"	TEXT NOT NULL UNIQUE,
+	PRIMARY KEY("name")
+);
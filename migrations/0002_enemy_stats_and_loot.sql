@@ -0,0 +1,24 @@
+-- Enemies get stats, a weapon, a level range, rewards and a loot table.
+-- Existing enemies keep their names and get the column defaults.
+CREATE TABLE "loot_tables" (
+	"name"	TEXT NOT NULL,
+	"item"	TEXT NOT NULL REFERENCES "weapons"("name"),
+	"chance"	NUMERIC NOT NULL,
+	PRIMARY KEY("name","item")
+);
+CREATE TABLE "enemies_new" (
+	"name"	TEXT NOT NULL UNIQUE,
+	"physique"	INTEGER NOT NULL DEFAULT 1,
+	"technique"	INTEGER NOT NULL DEFAULT 1,
+	"mystique"	INTEGER NOT NULL DEFAULT 1,
+	"weapon"	TEXT REFERENCES "weapons"("name"),
+	"min_level"	INTEGER NOT NULL DEFAULT 1,
+	"max_level"	INTEGER NOT NULL DEFAULT 1,
+	"xp"	INTEGER NOT NULL DEFAULT 0,
+	"gold"	INTEGER NOT NULL DEFAULT 0,
+	"loot"	TEXT,
+	PRIMARY KEY("name")
+);
+INSERT INTO "enemies_new" ("name") SELECT "name" FROM "enemies";
+DROP TABLE "enemies";
+ALTER TABLE "enemies_new" RENAME TO "enemies";
@@ -0,0 +1,17 @@
+-- Items that aren't weapons, and the effects that usable ones have.
+CREATE TABLE "effects" (
+	"name"	TEXT NOT NULL UNIQUE,
+	"kind"	TEXT NOT NULL,
+	"amount"	INTEGER NOT NULL DEFAULT 0,
+	PRIMARY KEY("name")
+);
+CREATE TABLE "items" (
+	"name"	TEXT NOT NULL UNIQUE,
+	"category"	TEXT NOT NULL,
+	"weight"	NUMERIC NOT NULL DEFAULT 0,
+	"value"	INTEGER NOT NULL DEFAULT 0,
+	"max_stack"	INTEGER NOT NULL DEFAULT 1,
+	"effect"	TEXT REFERENCES "effects"("name"),
+	"description"	TEXT NOT NULL DEFAULT '',
+	PRIMARY KEY("name")
+);
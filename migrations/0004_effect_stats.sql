@@ -0,0 +1,2 @@
+-- Buffs say which stat they raise.
+ALTER TABLE "effects" ADD COLUMN "stat" TEXT;
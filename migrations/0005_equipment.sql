@@ -0,0 +1,10 @@
+-- Armor and other equipment: where it's worn, how much it protects, and the
+-- stats needed to use it. Weapons get the same requirements.
+ALTER TABLE "items" ADD COLUMN "slot" TEXT;
+ALTER TABLE "items" ADD COLUMN "defense" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "items" ADD COLUMN "min_physique" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "items" ADD COLUMN "min_technique" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "items" ADD COLUMN "min_mystique" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "weapons" ADD COLUMN "min_physique" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "weapons" ADD COLUMN "min_technique" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "weapons" ADD COLUMN "min_mystique" INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- What an enemy looks like and what it says, for the battle screen.
+ALTER TABLE "enemies" ADD COLUMN "portrait" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "enemies" ADD COLUMN "intro" TEXT NOT NULL DEFAULT '';
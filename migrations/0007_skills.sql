@@ -0,0 +1,15 @@
+-- Skills and spells, paid for with stamina or mana, and how they're learned.
+CREATE TABLE "skills" (
+	"name"	TEXT NOT NULL UNIQUE,
+	"resource"	TEXT NOT NULL DEFAULT 'stamina',
+	"cost"	INTEGER NOT NULL DEFAULT 0,
+	"physique"	NUMERIC NOT NULL DEFAULT 0,
+	"technique"	NUMERIC NOT NULL DEFAULT 0,
+	"mystique"	NUMERIC NOT NULL DEFAULT 0,
+	"target"	TEXT NOT NULL DEFAULT 'enemy',
+	"effect"	TEXT REFERENCES "effects"("name"),
+	"level"	INTEGER,
+	"price"	INTEGER,
+	"description"	TEXT NOT NULL DEFAULT '',
+	PRIMARY KEY("name")
+);
@@ -0,0 +1,5 @@
+-- Status effects, which enemies can inflict and be immune to.
+ALTER TABLE "effects" ADD COLUMN "status" TEXT;
+ALTER TABLE "effects" ADD COLUMN "turns" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "enemies" ADD COLUMN "immunities" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "enemies" ADD COLUMN "inflicts" TEXT REFERENCES "effects"("name");
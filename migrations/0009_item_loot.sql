@@ -0,0 +1,11 @@
+-- Loot can be items as well as weapons, so it no longer references weapons.
+CREATE TABLE "loot_tables_new" (
+	"name"	TEXT NOT NULL,
+	"item"	TEXT NOT NULL,
+	"chance"	NUMERIC NOT NULL,
+	PRIMARY KEY("name","item")
+);
+INSERT INTO "loot_tables_new" ("name", "item", "chance")
+	SELECT "name", "item", "chance" FROM "loot_tables";
+DROP TABLE "loot_tables";
+ALTER TABLE "loot_tables_new" RENAME TO "loot_tables";
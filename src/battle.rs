@@ -0,0 +1,1059 @@
+use crate::combat::{area_attack, combat, strike, Attacker, Combatant, Defender};
+use crate::enemy::Enemy;
+use crate::inventory::Stack;
+use crate::items::{Contents, EffectKind, ItemCategory};
+use crate::player::Player;
+use crate::registry::ContentRegistry;
+use crate::rng::GameRng;
+use crate::skills::{Cost, Skill, SkillTarget};
+use crate::stats::Stats;
+use crate::status::{Status, StatusKind, Statuses};
+use crate::ui::{choice, greyed, Io};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Stamina lost each round by a player carrying more than they can manage.
+pub const ENCUMBRANCE_STAMINA: i32 = 5;
+
+/// Stamina spent on a heavy attack.
+pub const HEAVY_ATTACK_STAMINA: i32 = 15;
+/// Stamina spent on a sweep.
+pub const SWEEP_STAMINA: i32 = 10;
+/// Mana spent casting an arcane bolt.
+pub const BOLT_MANA: i32 = 12;
+/// How likely an enemy's hit is to inflict its status.
+pub const INFLICT_CHANCE: f64 = 0.3;
+
+/// How a battle ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BattleResult {
+    /// Every enemy was beaten. Holds what the player got for it, and the loot
+    /// they could carry.
+    Victory {
+        xp: i32,
+        gold: i32,
+        loot: Vec<Contents>,
+    },
+    Defeat,
+    Fled,
+}
+
+/// Someone taking part in a battle. Allies and enemies are indexes into the
+/// battle's `allies` and `enemies`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fighter {
+    Player,
+    Ally(usize),
+    Enemy(usize),
+}
+
+/// What the player picked to do this round.
+enum Action {
+    Attack(usize),
+    /// A slower, harder blow that costs stamina.
+    HeavyAttack(usize),
+    /// A spell that hits with the player's mystique, whatever they're holding.
+    Bolt(usize),
+    Sweep,
+    /// One of the player's skills, and the enemy it's aimed at if it needs one.
+    Skill(usize, Option<usize>),
+    /// An inventory stack, and the enemy it's aimed at if it needs one.
+    Item(usize, Option<usize>),
+    Flee,
+    /// Nothing, for a player who's stunned.
+    Pass,
+}
+
+/// Active statuses for the battle screen, like ` [Poison 3 (2t)]`.
+fn afflictions(statuses: &Statuses) -> String {
+    if statuses.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", statuses)
+    }
+}
+
+/// Joins names like `A, B and C`.
+fn names(names: &[&str]) -> String {
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        Some((last, _)) => last.to_string(),
+        None => String::new(),
+    }
+}
+
+/// A fight between the player, with any allies, and a group of enemies.
+///
+/// Everything shown about the enemies, down to their portraits and intro
+/// lines, comes from content, so any of them can be fought. `run` plays the
+/// fight out and leaves the player with whatever health and rewards they
+/// ended up with. Allies are computer controlled, and use the same stats
+/// and weapons as enemies do.
+pub struct Battle<'a> {
+    player: &'a mut Player,
+    allies: Vec<Enemy>,
+    enemies: Vec<Enemy>,
+    /// Everyone's health when the battle started, for the battle screen.
+    ally_health: Vec<i32>,
+    enemy_health: Vec<i32>,
+    can_flee: bool,
+    /// The player's skills, looked up in content when the battle starts.
+    skills: Vec<Skill>,
+}
+
+impl<'a> Battle<'a> {
+    pub fn new(player: &'a mut Player, enemy: Enemy) -> Self {
+        Self::against(player, vec![enemy])
+    }
+
+    /// A battle against a group of enemies. Enemies that share a name get a
+    /// letter each, like `Goblin A` and `Goblin B`, so they can be told apart.
+    pub fn against(player: &'a mut Player, mut enemies: Vec<Enemy>) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for enemy in enemies.iter() {
+            *counts.entry(enemy.name.clone()).or_default() += 1;
+        }
+        let mut seen: HashMap<String, u8> = HashMap::new();
+        for enemy in enemies.iter_mut() {
+            if counts[&enemy.name] > 1 {
+                let n = seen.entry(enemy.name.clone()).or_default();
+                enemy.name = format!("{} {}", enemy.name, (b'A' + *n) as char);
+                *n += 1;
+            }
+        }
+        Self {
+            player,
+            allies: Vec::new(),
+            enemy_health: enemies.iter().map(|e| e.health).collect(),
+            ally_health: Vec::new(),
+            enemies,
+            can_flee: true,
+            skills: Vec::new(),
+        }
+    }
+
+    /// Adds someone fighting on the player's side.
+    pub fn with_ally(mut self, ally: Enemy) -> Self {
+        self.ally_health.push(ally.health);
+        self.allies.push(ally);
+        self
+    }
+
+    /// Stops the player running away, for fights the story depends on.
+    pub fn no_escape(mut self) -> Self {
+        self.can_flee = false;
+        self
+    }
+
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    pub fn allies(&self) -> &[Enemy] {
+        &self.allies
+    }
+
+    /// The enemies still standing, by index.
+    fn standing(&self) -> Vec<usize> {
+        (0..self.enemies.len())
+            .filter(|&n| self.enemies[n].health > 0)
+            .collect()
+    }
+
+    /// Who acts when this round, everyone still standing from the highest
+    /// technique down. Enemies go before the player's side on a tie, and an
+    /// encumbered player always goes last.
+    pub fn turn_order(&self) -> Vec<Fighter> {
+        let mut order: Vec<(i32, Fighter)> = Vec::new();
+        for n in self.standing() {
+            order.push((self.enemies[n].stats.technique, Fighter::Enemy(n)));
+        }
+        if self.player.health > 0 {
+            let technique = if self.player.is_encumbered() {
+                i32::MIN
+            } else {
+                self.player.get_stats().technique
+            };
+            order.push((technique, Fighter::Player));
+        }
+        for (n, ally) in self.allies.iter().enumerate() {
+            if ally.health > 0 {
+                order.push((ally.stats.technique, Fighter::Ally(n)));
+            }
+        }
+        order.sort_by_key(|(technique, _)| std::cmp::Reverse(*technique));
+        order.into_iter().map(|(_, fighter)| fighter).collect()
+    }
+
+    /// Whether an enemy acts before the player this round.
+    pub fn enemy_first(&self) -> bool {
+        matches!(self.turn_order().first(), Some(Fighter::Enemy(_)))
+    }
+
+    /// The chance of getting away, better the quicker the player is than the
+    /// quickest enemy.
+    pub fn flee_chance(&self) -> f64 {
+        let fastest = self
+            .standing()
+            .iter()
+            .map(|&n| self.enemies[n].stats.technique)
+            .max()
+            .unwrap_or(0);
+        let edge = self.player.get_stats().technique - fastest;
+        (0.5 + 0.1 * f64::from(edge)).clamp(0.1, 0.9)
+    }
+
+    fn foes(&self) -> String {
+        let all: Vec<&str> = self.enemies.iter().map(|e| e.name.as_str()).collect();
+        names(&all)
+    }
+
+    fn screen(&self) -> String {
+        let mut lines = vec!["==========================".to_string()];
+        let first = if self.enemy_first() {
+            " (Strikes first)"
+        } else {
+            ""
+        };
+        match self.enemies.as_slice() {
+            [enemy] => {
+                if !enemy.portrait.is_empty() {
+                    lines.push(enemy.portrait.clone());
+                    lines.push(String::new());
+                }
+                lines.push(format!("{}{}", enemy.intro(), first));
+            }
+            _ => lines.push(format!("{} attack!{}", self.foes(), first)),
+        }
+        for (enemy, max) in self.enemies.iter().zip(self.enemy_health.iter()) {
+            if enemy.health > 0 {
+                lines.push(format!(
+                    "{} HP: {}/{}{}",
+                    enemy.name,
+                    enemy.health,
+                    max,
+                    afflictions(&enemy.statuses)
+                ));
+            } else {
+                lines.push(format!("{} is down", enemy.name));
+            }
+        }
+        lines.push(String::new());
+        for (ally, max) in self.allies.iter().zip(self.ally_health.iter()) {
+            lines.push(format!(
+                "{} HP: {}/{}{}",
+                ally.name,
+                ally.health.max(0),
+                max,
+                afflictions(&ally.statuses)
+            ));
+        }
+        lines.push(format!(
+            "HP: {}/{}\nSP: {}/{}\nMP: {}/{}",
+            self.player.health,
+            self.player.stats.max_health(),
+            self.player.stamina,
+            self.player.stats.max_stamina(),
+            self.player.mana,
+            self.player.stats.max_mana()
+        ));
+        if !self.player.statuses.is_empty() {
+            lines.push(format!("Status: {}", self.player.statuses));
+        }
+        lines.push("==========================".into());
+        lines.join("\n")
+    }
+
+    /// Asks which enemy to aim at, unless there's only one left.
+    fn choose_target(&self, io: &mut dyn Io, prompt: &str) -> Option<usize> {
+        let standing = self.standing();
+        if standing.len() == 1 {
+            return Some(standing[0]);
+        }
+        let options: Vec<&str> = standing
+            .iter()
+            .map(|&n| self.enemies[n].name.as_str())
+            .collect();
+        let c = choice(io, prompt, &options, true);
+        if c < 0 {
+            return None;
+        }
+        Some(standing[c as usize])
+    }
+
+    /// Lets the player pick something to use, and what to aim it at.
+    fn choose_item(&self, io: &mut dyn Io) -> Option<Action> {
+        let inventory = &self.player.inventory;
+        let usable: Vec<usize> = inventory
+            .filter(Some(ItemCategory::Consumable))
+            .into_iter()
+            .filter(|&n| matches!(&inventory.stacks()[n].contents, Contents::Item(i) if i.usable()))
+            .collect();
+        if usable.is_empty() {
+            io.any_key("You dont have any items you can use!");
+            return None;
+        }
+        let options: Vec<&Stack> = usable.iter().map(|&n| &inventory.stacks()[n]).collect();
+        let c = choice(io, "Use which item? It takes your turn.", &options, true);
+        if c < 0 {
+            return None;
+        }
+        let index = usable[c as usize];
+        let aimed = match &inventory.stacks()[index].contents {
+            Contents::Item(i) => i.effect.as_ref().is_some_and(|e| {
+                e.kind == EffectKind::Damage || e.status.is_some_and(|s| s.harmful())
+            }),
+            Contents::Weapon(_) => false,
+        };
+        let target = if aimed {
+            Some(self.choose_target(io, "Throw it at who?")?)
+        } else {
+            None
+        };
+        Some(Action::Item(index, target))
+    }
+
+    /// What `action` costs the player.
+    fn cost(&self, action: &Action) -> Cost {
+        match *action {
+            Action::HeavyAttack(_) => Cost::Stamina(HEAVY_ATTACK_STAMINA),
+            Action::Bolt(_) => Cost::Mana(BOLT_MANA),
+            Action::Sweep => Cost::Stamina(SWEEP_STAMINA),
+            Action::Skill(n, _) => self.skills[n].cost,
+            Action::Attack(_) | Action::Item(..) | Action::Flee | Action::Pass => Cost::Free,
+        }
+    }
+
+    /// Lets the player pick one of their skills, and what to aim it at.
+    fn choose_skill(&self, io: &mut dyn Io) -> Option<Action> {
+        let skills = &self.skills;
+        if skills.is_empty() {
+            io.any_key("You haven't learned any skills yet.");
+            return None;
+        }
+        let options: Vec<String> = skills
+            .iter()
+            .map(|s| {
+                if s.cost.affordable(self.player) {
+                    s.to_string()
+                } else {
+                    greyed(&format!("{} - can't afford", s))
+                }
+            })
+            .collect();
+        let c = choice(io, "Use which skill?", &options, true);
+        if c < 0 {
+            return None;
+        }
+        let skill = &skills[c as usize];
+        if !skill.cost.affordable(self.player) {
+            io.any_key(skill.cost.refusal());
+            return None;
+        }
+        let target = match skill.target {
+            SkillTarget::Enemy => Some(self.choose_target(io, "Use it on who?")?),
+            SkillTarget::All | SkillTarget::Myself => None,
+        };
+        Some(Action::Skill(c as usize, target))
+    }
+
+    /// The battle menu. Anything the player can't afford is greyed out.
+    fn options(&self) -> Vec<(String, Cost)> {
+        let mut options = vec![
+            ("Attack", Cost::Free),
+            ("Item", Cost::Free),
+            ("Flee", Cost::Free),
+            ("Heavy Attack", Cost::Stamina(HEAVY_ATTACK_STAMINA)),
+            ("Arcane Bolt", Cost::Mana(BOLT_MANA)),
+            ("Skills", Cost::Free),
+        ];
+        if self.standing().len() > 1 {
+            options.push(("Sweep", Cost::Stamina(SWEEP_STAMINA)));
+        }
+        options
+            .into_iter()
+            .map(|(name, cost)| {
+                let text = format!("{}{}", name, cost.label());
+                if cost.affordable(self.player) {
+                    (text, cost)
+                } else {
+                    (greyed(&format!("{} - can't afford", text)), cost)
+                }
+            })
+            .collect()
+    }
+
+    /// Shows the battle screen until the player settles on something they can
+    /// afford to do. It's paid for when their turn comes.
+    fn choose_action(&self, io: &mut dyn Io) -> Action {
+        loop {
+            let options = self.options();
+            let texts: Vec<&str> = options.iter().map(|(text, _)| text.as_str()).collect();
+            let c = choice(io, &self.screen(), &texts, false);
+            let cost = options
+                .get(c as usize)
+                .map_or(Cost::Free, |(_, cost)| *cost);
+            if !cost.affordable(self.player) {
+                io.any_key(cost.refusal());
+                continue;
+            }
+            let action = match c {
+                0 => self.choose_target(io, "Attack who?").map(Action::Attack),
+                1 => self.choose_item(io),
+                2 if !self.can_flee => {
+                    io.any_key(&format!(
+                        "You try to flee, but the {} overpowers you, and forces you to fight!",
+                        self.foes()
+                    ));
+                    None
+                }
+                2 => Some(Action::Flee),
+                3 => self
+                    .choose_target(io, "Attack who?")
+                    .map(Action::HeavyAttack),
+                4 => self.choose_target(io, "Cast at who?").map(Action::Bolt),
+                5 => self.choose_skill(io),
+                _ => Some(Action::Sweep),
+            };
+            if let Some(action) = action {
+                return action;
+            }
+        }
+    }
+
+    /// Costs an encumbered player some stamina for the round, saying so.
+    fn strain(&mut self, io: &mut dyn Io) {
+        if self.player.is_encumbered() {
+            self.player.stamina = (self.player.stamina - ENCUMBRANCE_STAMINA).max(0);
+            io.print(&format!(
+                "You struggle under your load, losing {} stamina.",
+                ENCUMBRANCE_STAMINA
+            ));
+        }
+    }
+
+    /// `target`, or the first enemy still standing if it's already down.
+    fn retarget(&self, target: usize) -> Option<usize> {
+        if self.enemies[target].health > 0 {
+            Some(target)
+        } else {
+            self.standing().first().copied()
+        }
+    }
+
+    /// Pays for and does what the player picked, returning whether they got away.
+    fn player_turn(&mut self, action: &Action, io: &mut dyn Io, rng: &mut GameRng) -> bool {
+        self.cost(action).pay(self.player);
+        self.strain(io);
+        match *action {
+            Action::Attack(target) => {
+                if let Some(t) = self.retarget(target) {
+                    io.print(&combat(&*self.player, &mut self.enemies[t]).to_string());
+                }
+            }
+            Action::HeavyAttack(target) => {
+                if let Some(t) = self.retarget(target) {
+                    let attack = self.player.damage() * 3 / 2;
+                    let outcome = strike(&*self.player, &mut self.enemies[t], attack);
+                    io.print(&format!("A heavy blow! {}", outcome));
+                }
+            }
+            Action::Bolt(target) => {
+                if let Some(t) = self.retarget(target) {
+                    let attack = (self.player.get_stats().mystique * 3
+                        - self.player.statuses.attack_penalty())
+                    .max(0);
+                    let outcome = strike(&*self.player, &mut self.enemies[t], attack);
+                    io.print(&format!("You cast Arcane Bolt! {}", outcome));
+                }
+            }
+            Action::Skill(index, target) => {
+                let skill = self.skills[index].clone();
+                io.print(&format!("You use {}!", skill.name));
+                let hit = match skill.target {
+                    SkillTarget::Enemy => {
+                        target.and_then(|t| self.retarget(t)).into_iter().collect()
+                    }
+                    SkillTarget::All => self.standing(),
+                    SkillTarget::Myself => Vec::new(),
+                };
+                let power = (skill.power(&self.player.get_stats())
+                    - self.player.statuses.attack_penalty())
+                .max(0);
+                if power > 0 {
+                    for &t in hit.iter() {
+                        io.print(&strike(&*self.player, &mut self.enemies[t], power).to_string());
+                    }
+                }
+                if let Some(effect) = &skill.effect {
+                    let targets = self
+                        .enemies
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(n, e)| hit.contains(n) && e.health > 0)
+                        .map(|(_, e)| e as &mut dyn Defender)
+                        .collect();
+                    io.print(&self.player.apply(effect, targets));
+                }
+            }
+            Action::Sweep => {
+                let standing = self
+                    .enemies
+                    .iter_mut()
+                    .filter(|e| e.health > 0)
+                    .map(|e| e as &mut dyn Defender);
+                for outcome in area_attack(&*self.player, standing) {
+                    io.print(&outcome.to_string());
+                }
+            }
+            Action::Item(index, target) => {
+                let targets: Vec<&mut dyn Defender> = match target.and_then(|t| self.retarget(t)) {
+                    Some(t) => vec![&mut self.enemies[t]],
+                    None => self
+                        .enemies
+                        .iter_mut()
+                        .filter(|e| e.health > 0)
+                        .map(|e| e as &mut dyn Defender)
+                        .collect(),
+                };
+                if let Some(msg) = self.player.use_item_in_battle(index, targets) {
+                    io.print(&msg);
+                }
+            }
+            Action::Flee => {
+                if rng.gen_bool(self.flee_chance()) {
+                    io.any_key("You got away!");
+                    return true;
+                }
+                io.print("You couldn't get away!");
+            }
+            Action::Pass => {}
+        }
+        false
+    }
+
+    /// An ally goes for whichever enemy is closest to falling.
+    fn ally_turn(&mut self, ally: usize, io: &mut dyn Io) {
+        let target = self
+            .standing()
+            .into_iter()
+            .min_by_key(|&n| self.enemies[n].health);
+        if let Some(t) = target {
+            io.print(&combat(&self.allies[ally], &mut self.enemies[t]).to_string());
+        }
+    }
+
+    /// An enemy attacks the player or one of their allies, at random.
+    fn enemy_turn(&mut self, enemy: usize, io: &mut dyn Io, rng: &mut GameRng) {
+        let mut targets = vec![None];
+        targets.extend(
+            (0..self.allies.len())
+                .filter(|&n| self.allies[n].health > 0)
+                .map(Some),
+        );
+        let picked = targets[rng.gen_range(0, targets.len())];
+        let attacker = &self.enemies[enemy];
+        let target: &mut dyn Defender = match picked {
+            None => &mut *self.player,
+            Some(a) => &mut self.allies[a],
+        };
+        io.print(&combat(attacker, &mut *target).to_string());
+        let status = attacker
+            .inflicts
+            .as_ref()
+            .and_then(|e| e.status.map(|kind| Status::new(kind, e.amount, e.turns)));
+        if let Some(status) = status {
+            if rng.gen_bool(INFLICT_CHANCE) && target.afflict(status) {
+                io.print(&format!("{} suffers {}!", target.name(), status.kind));
+            }
+        }
+        if let Some(a) = picked {
+            if self.allies[a].health < 1 {
+                io.print(&format!("{} falls!", self.allies[a].name));
+            }
+        }
+    }
+
+    /// Ticks `fighter`'s statuses at the start of their turn, saying what
+    /// they did. Returns whether the fighter can still act.
+    fn start_turn(&mut self, fighter: Fighter, io: &mut dyn Io) -> bool {
+        let (name, statuses, health, max) = match fighter {
+            Fighter::Player => {
+                let max = self.player.stats.max_health();
+                let p = &mut *self.player;
+                (&p.name, &mut p.statuses, &mut p.health, max)
+            }
+            Fighter::Ally(n) => {
+                let a = &mut self.allies[n];
+                (&a.name, &mut a.statuses, &mut a.health, self.ally_health[n])
+            }
+            Fighter::Enemy(n) => {
+                let e = &mut self.enemies[n];
+                (
+                    &e.name,
+                    &mut e.statuses,
+                    &mut e.health,
+                    self.enemy_health[n],
+                )
+            }
+        };
+        if statuses.is_empty() {
+            return true;
+        }
+        let tick = statuses.tick();
+        if tick.damage > 0 {
+            *health -= tick.damage;
+            io.print(&format!(
+                "{} suffers {} damage from their afflictions.",
+                name, tick.damage
+            ));
+        }
+        if tick.healing > 0 {
+            let before = *health;
+            *health = (*health + tick.healing).min(max).max(before);
+            io.print(&format!(
+                "{} regenerates {} health.",
+                name,
+                *health - before
+            ));
+        }
+        if *health < 1 {
+            io.print(&format!("{} falls!", name));
+            return false;
+        }
+        if tick.stunned {
+            io.print(&format!("{} is stunned, and can't act!", name));
+            return false;
+        }
+        true
+    }
+
+    /// Plays one round, everyone acting in `turn_order`. Returns whether the
+    /// player got away.
+    fn round(&mut self, action: Action, io: &mut dyn Io, rng: &mut GameRng) -> bool {
+        for fighter in self.turn_order() {
+            if self.player.health < 1 || self.standing().is_empty() {
+                break;
+            }
+            let standing = match fighter {
+                Fighter::Player => true,
+                Fighter::Ally(n) => self.allies[n].health > 0,
+                Fighter::Enemy(n) => self.enemies[n].health > 0,
+            };
+            if !standing || !self.start_turn(fighter, io) {
+                continue;
+            }
+            let fled = match fighter {
+                Fighter::Player => self.player_turn(&action, io, rng),
+                Fighter::Ally(n) => {
+                    self.ally_turn(n, io);
+                    false
+                }
+                Fighter::Enemy(n) => {
+                    self.enemy_turn(n, io, rng);
+                    false
+                }
+            };
+            if fled {
+                return true;
+            }
+        }
+        if self.player.health > 0 {
+            self.player.regen();
+        }
+        io.any_key("");
+        false
+    }
+
+    /// Hands over every enemy's xp, gold and whatever loot the player can carry.
+    fn rewards(
+        &mut self,
+        io: &mut dyn Io,
+        content: &ContentRegistry,
+        rng: &mut GameRng,
+    ) -> BattleResult {
+        let xp = self.enemies.iter().map(|e| e.xp).sum();
+        let gold = self.enemies.iter().map(|e| e.gold).sum();
+        self.player.money += gold;
+        let mut msg = format!("You gain {} xp and ${}.", xp, gold);
+        for line in self.player.gain_xp(xp, content) {
+            msg += &format!("\n{}", line);
+        }
+        let mut loot = Vec::new();
+        for enemy in self.enemies.iter() {
+            let drops = match &enemy.loot {
+                Some(table) => content.roll_loot(table, rng),
+                None => Vec::new(),
+            };
+            for drop in drops {
+                let name = drop.item().name.clone();
+                if self.player.pick_up(drop.clone(), 1) {
+                    msg += &format!("\nYou found a {}!", name);
+                    loot.push(drop);
+                } else {
+                    msg += &format!(
+                        "\nThe {} dropped a {}, but you can't carry any more.",
+                        enemy.name, name
+                    );
+                }
+            }
+        }
+        io.any_key(&msg);
+        BattleResult::Victory { xp, gold, loot }
+    }
+
+    /// Plays the battle out until the player falls, every enemy does, or the
+    /// player gets away. Buffs and statuses wear off when it's over.
+    pub fn run(
+        mut self,
+        io: &mut dyn Io,
+        content: &ContentRegistry,
+        rng: &mut GameRng,
+    ) -> BattleResult {
+        self.skills = self
+            .player
+            .skills
+            .iter()
+            .filter_map(|name| content.skill(name).cloned())
+            .collect();
+        let result = loop {
+            if self.player.health < 1 {
+                io.clear();
+                io.print(&self.screen());
+                io.any_key(&format!("You were beaten by the {}...", self.foes()));
+                break BattleResult::Defeat;
+            }
+            if self.standing().is_empty() {
+                io.clear();
+                io.print(&self.screen());
+                let beaten = if self.enemies.len() == 1 { "is" } else { "are" };
+                io.any_key(&format!(
+                    "You win! The {} {} defeated!",
+                    self.foes(),
+                    beaten
+                ));
+                break self.rewards(io, content, rng);
+            }
+            let action = if self.player.statuses.get(StatusKind::Stun).is_some() {
+                io.clear();
+                io.print(&self.screen());
+                io.any_key("You're stunned, and can't do anything this round!");
+                Action::Pass
+            } else {
+                self.choose_action(io)
+            };
+            if self.round(action, io, rng) {
+                break BattleResult::Fled;
+            }
+        };
+        self.player.boost = Stats::default();
+        self.player.statuses = Statuses::new();
+        result
+    }
+}
+
+mod test {
+    #[test]
+    fn scripted_battle_until_enemy_dies() {
+        use crate::battle::{Battle, BattleResult};
+        use crate::ui::ScriptedIo;
+        use crate::{Enemy, GameRng, Player, Stats};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        let bunny = content.enemy("Rabbit").unwrap().clone();
+        let mut io = ScriptedIo::new(&[0, 0, 0, 0]);
+        let result = Battle::new(&mut player, bunny).no_escape().run(
+            &mut io,
+            &content,
+            &mut GameRng::from_seed(1),
+        );
+        assert!(matches!(result, BattleResult::Victory { .. }));
+        let transcript = io.transcript();
+        assert!(transcript.contains("(>.<)"));
+        assert!(transcript.contains("You win!"));
+
+        // Enemies without a portrait or intro still get a battle screen.
+        let mut io = ScriptedIo::new(&[0, 0, 0, 0]);
+        let slug = Enemy::new("Slug").with_stats(1, 1, 1);
+        Battle::new(&mut player, slug).run(&mut io, &content, &mut GameRng::from_seed(1));
+        assert!(io.transcript().contains("Slug is about to strike!"));
+    }
+
+    #[test]
+    fn using_an_item_takes_a_turn() {
+        use crate::battle::Battle;
+        use crate::items::Contents;
+        use crate::ui::ScriptedIo;
+        use crate::{Enemy, GameRng, Player, Stats};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        player
+            .inventory
+            .add(Contents::Item(content.item("Firebomb").unwrap().clone()), 1);
+        player.inventory.add(
+            Contents::Item(content.item("Strength Elixir").unwrap().clone()),
+            1,
+        );
+        let ogre = Enemy::new("Ogre").with_stats(5, 1, 1);
+        let health = ogre.health;
+        // Item -> the bomb, then Item -> the elixir, then attack until it's over.
+        let mut io = ScriptedIo::new(&[1, 0, 1, 0, 0, 0, 0, 0, 0]);
+        Battle::new(&mut player, ogre).run(&mut io, &content, &mut GameRng::from_seed(1));
+        let transcript = io.transcript();
+        assert!(transcript.contains("It hits Ogre for 25 damage!"));
+        assert!(transcript.contains("Ogre attacked Hero"));
+        assert!(transcript.contains("Physique rises by 3"));
+        assert!(player.inventory.is_empty());
+        assert!(health > 25);
+        // Buffs wear off when the battle ends.
+        assert_eq!(player.boost, Stats::default());
+    }
+
+    #[test]
+    fn encumbered_players_act_last() {
+        use crate::battle::Battle;
+        use crate::items::{Contents, Item};
+        use crate::ui::ScriptedIo;
+        use crate::{Enemy, GameRng, Player, Stats};
+        let mut player = Player::new("Packrat", Stats::new(10, 10, 10));
+        player
+            .inventory
+            .add(Contents::Item(Item::new("Anvil", 100.0, 1)), 1);
+        let stamina = player.stamina;
+        let bunny = Enemy::new("Rabbit").with_stats(1, 1, 1);
+        let mut io = ScriptedIo::new(&[0, 0, 0, 0]);
+        Battle::new(&mut player, bunny).run(
+            &mut io,
+            &crate::registry::test_content(),
+            &mut GameRng::from_seed(1),
+        );
+        let transcript = io.transcript();
+        assert!(transcript.contains("struggle under your load"));
+        assert!(
+            transcript.find("Rabbit attacked Packrat").unwrap()
+                < transcript.find("Packrat attacked Rabbit").unwrap()
+        );
+        assert!(player.stamina < stamina);
+    }
+
+    #[test]
+    fn victory_pays_out_and_fleeing_ends_the_battle() {
+        use crate::battle::{Battle, BattleResult};
+        use crate::ui::ScriptedIo;
+        use crate::{GameRng, Player, Stats};
+        let content = crate::registry::test_content();
+        let orc = content.enemy("Orc").unwrap().clone();
+        let mut player = Player::new("Hero", Stats::new(20, 20, 20));
+        let mut io = ScriptedIo::new(&[0; 10]);
+        let result = Battle::new(&mut player, orc.clone()).run(
+            &mut io,
+            &content,
+            &mut GameRng::from_seed(3),
+        );
+        match result {
+            BattleResult::Victory { xp, gold, loot } => {
+                assert_eq!((xp, gold), (orc.xp, orc.gold));
+                assert_eq!(player.xp, orc.xp);
+                assert_eq!(player.money, 100 + orc.gold);
+                assert_eq!(player.inventory.len(), loot.len());
+            }
+            other => panic!("expected a victory, got {:?}", other),
+        }
+
+        let battle = Battle::new(&mut player, orc.clone());
+        assert_eq!(battle.flee_chance(), 0.9);
+        let mut io = ScriptedIo::new(&[2; 10]);
+        let result = battle.run(&mut io, &content, &mut GameRng::from_seed(3));
+        assert_eq!(result, BattleResult::Fled);
+        assert!(io.transcript().contains("You got away!"));
+    }
+
+    #[test]
+    fn groups_take_turns_by_technique() {
+        use crate::battle::{Battle, Fighter};
+        use crate::{Enemy, Player, Stats};
+        let mut player = Player::new("Hero", Stats::new(5, 5, 5));
+        let goblin = Enemy::new("Goblin").with_stats(1, 6, 1);
+        let slow = Enemy::new("Goblin").with_stats(1, 5, 1);
+        let squire = Enemy::new("Squire").with_stats(3, 2, 1);
+        let battle = Battle::against(&mut player, vec![goblin, slow]).with_ally(squire);
+        let names: Vec<&str> = battle.enemies().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Goblin A", "Goblin B"]);
+        assert_eq!(
+            battle.turn_order(),
+            vec![
+                Fighter::Enemy(0),
+                Fighter::Enemy(1),
+                Fighter::Player,
+                Fighter::Ally(0)
+            ]
+        );
+    }
+
+    #[test]
+    fn special_attacks_cost_stamina_and_mana() {
+        use crate::battle::{Battle, HEAVY_ATTACK_STAMINA};
+        use crate::ui::ScriptedIo;
+        use crate::{Enemy, GameRng, Player, Stats};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        player.mana = 0;
+        let stamina = player.stamina;
+        let mut troll = Enemy::new("Troll").with_stats(1, 1, 1);
+        troll.health = 1000;
+        // Heavy Attack, then Arcane Bolt without the mana, then flee.
+        let mut io = ScriptedIo::new(&[3, 4, 2, 2, 2, 2, 2, 2]);
+        Battle::new(&mut player, troll).run(&mut io, &content, &mut GameRng::from_seed(1));
+        let transcript = io.transcript();
+        assert!(transcript.contains("A heavy blow! Hero attacked Troll"));
+        assert!(transcript.contains("Arcane Bolt (12 MP) - can't afford"));
+        assert!(transcript.contains("You don't have enough mana."));
+        assert!(!transcript.contains("You cast Arcane Bolt!"));
+        let regen = player.stats.stamina_regen();
+        assert!(player.stamina <= stamina - HEAVY_ATTACK_STAMINA + 2 * regen);
+        assert!(player.mana > 0);
+    }
+
+    #[test]
+    fn a_skipped_turn_costs_nothing() {
+        use crate::battle::{Action, Battle};
+        use crate::ui::ScriptedIo;
+        use crate::{Defender, Enemy, GameRng, Player, Stats, Status, StatusKind};
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        player.afflict(Status::new(StatusKind::Stun, 0, 1));
+        player.stamina = 20;
+        let troll = Enemy::new("Troll").with_stats(1, 1, 1);
+        let mut battle = Battle::new(&mut player, troll);
+        let mut io = ScriptedIo::new(&[]);
+        battle.round(Action::HeavyAttack(0), &mut io, &mut GameRng::from_seed(1));
+        assert!(io.transcript().contains("Hero is stunned, and can't act!"));
+        assert!(!io.transcript().contains("A heavy blow!"));
+        // Only the round's regeneration, with nothing spent.
+        assert_eq!(player.stamina, 20 + player.stats.stamina_regen());
+    }
+
+    #[test]
+    fn stunned_and_weakened_players() {
+        use crate::battle::Battle;
+        use crate::ui::ScriptedIo;
+        use crate::{Defender, Enemy, GameRng, Player, Stats, Status, StatusKind};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        player.afflict(Status::new(StatusKind::Stun, 0, 1));
+        player.afflict(Status::new(StatusKind::AttackDown, 10, 3));
+        let mut troll = Enemy::new("Troll").with_stats(1, 1, 1);
+        troll.health = 1000;
+        // No choice while stunned, then Arcane Bolt, then flee.
+        let mut io = ScriptedIo::new(&[4, 2, 2, 2, 2, 2, 2]);
+        Battle::new(&mut player, troll).run(&mut io, &content, &mut GameRng::from_seed(1));
+        let transcript = io.transcript();
+        assert!(transcript.contains("You're stunned, and can't do anything this round!"));
+        // 30 from mystique, less 10 for Attack down and 2 for the Troll's defense.
+        assert!(transcript.contains("You cast Arcane Bolt! Hero attacked Troll, for 18 damage!"));
+    }
+
+    #[test]
+    fn skills_cost_resources_and_hit_their_targets() {
+        use crate::battle::Battle;
+        use crate::ui::ScriptedIo;
+        use crate::{Enemy, GameRng, Player, Stats};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Mage", Stats::new(5, 5, 20));
+        player.learn("Mend");
+        player.learn("Chain Lightning");
+        player.health = 10;
+        player.mana = 45;
+        let slimes = vec![
+            Enemy::new("Slime").with_stats(1, 1, 1),
+            Enemy::new("Slime").with_stats(1, 1, 1),
+        ];
+        // Skills -> Mend, then Skills -> Chain Lightning, then attack.
+        let mut script = vec![5, 0, 5, 1];
+        script.extend([0; 10].iter());
+        let mut io = ScriptedIo::new(&script);
+        Battle::against(&mut player, slimes).run(&mut io, &content, &mut GameRng::from_seed(2));
+        let transcript = io.transcript();
+        assert!(transcript.contains("You use Mend!"));
+        assert!(transcript.contains("You recover"));
+        assert!(transcript.contains("You use Chain Lightning!"));
+        assert!(transcript.contains("Mage attacked Slime A"));
+        assert!(transcript.contains("Mage attacked Slime B"));
+        // Mend's 15 MP is paid, then a round's worth comes back.
+        let regen = player.stats.mana_regen();
+        assert!(transcript.contains(&format!("MP: {}/", 45 - 15 + regen)));
+    }
+
+    #[test]
+    fn statuses_tick_and_respect_immunities() {
+        use crate::battle::Battle;
+        use crate::items::Contents;
+        use crate::ui::ScriptedIo;
+        use crate::{Defender, Enemy, GameRng, Player, Stats, Status, StatusKind};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        player.inventory.add(
+            Contents::Item(content.item("Poison Vial").unwrap().clone()),
+            2,
+        );
+        let defense = player.defense();
+        player.afflict(Status::new(StatusKind::DefenseUp, 5, 1));
+        assert_eq!(player.defense(), defense + 5);
+
+        let mut ghoul = Enemy::new("Ghoul").with_stats(1, 1, 1);
+        ghoul.immune.push(StatusKind::Poison);
+        let mut rat = Enemy::new("Rat").with_stats(1, 1, 1);
+        rat.afflict(Status::new(StatusKind::Stun, 0, 1));
+        // Item -> a vial -> the Ghoul, then the Rat, then attack until it's over.
+        let mut script = vec![1, 0, 0, 1, 0, 1];
+        script.extend([0; 30].iter());
+        let mut io = ScriptedIo::new(&script);
+        Battle::against(&mut player, vec![ghoul, rat]).run(
+            &mut io,
+            &content,
+            &mut GameRng::from_seed(4),
+        );
+        let transcript = io.transcript();
+        assert!(transcript.contains("Rat is stunned, and can't act!"));
+        assert!(transcript.contains("Ghoul is immune to Poison."));
+        assert!(transcript.contains("Rat suffers Poison!"));
+        assert!(transcript.contains("Rat HP: 29/32 [Poison 3 (2t)]"));
+        assert!(transcript.contains("Rat suffers 3 damage from their afflictions."));
+        // Statuses don't outlast the battle.
+        assert!(player.statuses.is_empty());
+    }
+
+    #[test]
+    fn party_against_a_group() {
+        use crate::battle::{Battle, BattleResult};
+        use crate::items::Contents;
+        use crate::ui::ScriptedIo;
+        use crate::{Enemy, GameRng, Player, Stats};
+        let content = crate::registry::test_content();
+        let mut player = Player::new("Hero", Stats::new(10, 10, 10));
+        player.inventory.add(
+            Contents::Item(content.item("Powder Keg").unwrap().clone()),
+            1,
+        );
+        let slimes = vec![
+            Enemy::new("Slime").with_stats(1, 1, 1),
+            Enemy::new("Slime").with_stats(1, 1, 1),
+            Enemy::new("Bat").with_stats(1, 1, 1),
+        ];
+        let squire = Enemy::new("Squire").with_stats(4, 1, 1);
+        // Item -> the keg, then Attack -> Slime B, then Sweep until it's over.
+        let mut script = vec![1, 0, 0, 1];
+        script.extend([6; 20].iter());
+        let mut io = ScriptedIo::new(&script);
+        let result = Battle::against(&mut player, slimes).with_ally(squire).run(
+            &mut io,
+            &content,
+            &mut GameRng::from_seed(5),
+        );
+        assert!(matches!(result, BattleResult::Victory { .. }));
+        let transcript = io.transcript();
+        assert!(transcript.contains("It hits Slime A for 15 damage! It hits Slime B for 15 damage! It hits Bat for 15 damage!"));
+        assert!(transcript.contains("Hero attacked Slime B"));
+        assert!(transcript.contains("Squire attacked"));
+        assert!(transcript.contains("The Slime A, Slime B and Bat are defeated!"));
+    }
+}
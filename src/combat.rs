@@ -0,0 +1,106 @@
+use crate::stats::Stats;
+use crate::status::Status;
+
+pub trait Combatant {
+    fn get_stats(&self) -> Stats;
+    fn name(&self) -> &str;
+}
+
+pub trait Attacker: Combatant {
+    fn damage(&self) -> i32 {
+        0
+    }
+}
+
+pub trait Defender: Combatant {
+    fn defense(&self) -> i32 {
+        0
+    }
+    fn take_damage(&mut self, damage: i32);
+    /// Puts a status on them, returning false if they're immune to it.
+    fn afflict(&mut self, _status: Status) -> bool {
+        false
+    }
+}
+
+pub struct BattleOutcome<'a> {
+    pub attacker: &'a dyn Attacker,
+    pub defender: &'a dyn Defender,
+    pub damage: i32,
+}
+
+impl<'a> BattleOutcome<'a> {
+    pub fn print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl<'a> std::fmt::Display for BattleOutcome<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "{} attacked {}, for {} damage!",
+            self.attacker.name(),
+            self.defender.name(),
+            self.damage
+        ))
+    }
+}
+
+/// Hits `defender` for `attack` less its defense, but never less than 1.
+pub fn strike<'a>(
+    attacker: &'a dyn Attacker,
+    defender: &'a mut dyn Defender,
+    attack: i32,
+) -> BattleOutcome<'a> {
+    let damage = (attack - defender.defense()).max(1);
+    defender.take_damage(damage);
+    BattleOutcome {
+        attacker,
+        defender,
+        damage,
+    }
+}
+
+pub fn combat<'a>(attacker: &'a dyn Attacker, defender: &'a mut dyn Defender) -> BattleOutcome<'a> {
+    strike(attacker, defender, attacker.damage())
+}
+
+/// Attacks every one of `defenders` at once, each for half the usual damage.
+pub fn area_attack<'a, I>(attacker: &'a dyn Attacker, defenders: I) -> Vec<BattleOutcome<'a>>
+where
+    I: IntoIterator<Item = &'a mut dyn Defender>,
+{
+    let attack = attacker.damage() / 2;
+    defenders
+        .into_iter()
+        .map(|defender| strike(attacker, defender, attack))
+        .collect()
+}
+
+mod test {
+    #[test]
+    fn every_hit_does_at_least_one_damage() {
+        use crate::{combat, Enemy, Player, Stats};
+        let mut knight = Player::new("Knight", Stats::new(30, 1, 1));
+        let health = knight.health;
+        let mouse = Enemy::new("Mouse").with_stats(1, 1, 1);
+        let outcome = combat(&mouse, &mut knight);
+        assert_eq!(outcome.damage, 1);
+        assert_eq!(outcome.to_string(), "Mouse attacked Knight, for 1 damage!");
+        assert_eq!(knight.health, health - 1);
+    }
+
+    #[test]
+    fn area_attacks_hit_everyone_for_half() {
+        use crate::combat::area_attack;
+        use crate::{combat, Defender, Enemy, Player, Stats};
+        let hero = Player::new("Hero", Stats::new(10, 1, 1));
+        let mut a = Enemy::new("A").with_stats(1, 1, 1);
+        let mut b = Enemy::new("B").with_stats(1, 1, 1);
+        let full = combat(&hero, &mut a.clone()).damage;
+        let outcomes = area_attack(&hero, vec![&mut a as &mut dyn Defender, &mut b]);
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|o| o.damage < full));
+        assert_eq!(a.health, b.health);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::error::{QrpgError, Result};
+use crate::persistence::PLAYERS;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where the game keeps its data, and how it should run.
+///
+/// Each setting comes from the first of these that has it:
+///
+/// 1. command line flags: `--db`, `--saves`, `--store`, `--seed`, `--config`, `--hot-reload`
+/// 2. environment variables: `QRPG_DB`, `QRPG_SAVE_DIR`, `QRPG_STORE`, `QRPG_SEED`,
+///    `QRPG_CONFIG`, `QRPG_HOT_RELOAD`
+/// 3. the config file, `$XDG_CONFIG_HOME/qrpg/config.json` unless `--config` says otherwise
+/// 4. defaults under the XDG data dir, `$XDG_DATA_HOME/qrpg` or `~/.local/share/qrpg`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The content database, `qrpg.db`.
+    pub db_path: PathBuf,
+    /// Where the JSON save store keeps its character folders.
+    pub save_dir: PathBuf,
+    /// Which save store to use: `json` or `sqlite`.
+    pub store: String,
+    pub seed: Option<u64>,
+    /// Reload content when the database changes, for editing it while playing.
+    pub hot_reload: bool,
+}
+
+/// The optional config file. Relative paths in it are relative to the file.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub db: Option<PathBuf>,
+    pub saves: Option<PathBuf>,
+    pub store: Option<String>,
+    pub seed: Option<u64>,
+    pub hot_reload: Option<bool>,
+}
+
+/// The value following `flag` on the command line, e.g. `--seed 42`.
+pub fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == flag)?;
+    args.get(i + 1).cloned()
+}
+
+/// The flags that `Config` reads a value after.
+const VALUE_FLAGS: &[&str] = &["--config", "--db", "--saves", "--store", "--seed"];
+
+/// The command line without the flags `Config` reads, leaving the subcommand
+/// and its own arguments wherever the flags were put.
+pub fn positional(args: &[String]) -> Vec<String> {
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            args.next();
+        } else if arg != "--hot-reload" {
+            rest.push(arg.clone());
+        }
+    }
+    rest
+}
+
+fn home(env: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    env("HOME").filter(|h| !h.is_empty()).map(PathBuf::from)
+}
+
+/// An XDG base directory: `$<var>` if set, otherwise `~/<fallback>`.
+fn xdg_dir(env: &dyn Fn(&str) -> Option<String>, var: &str, fallback: &str) -> PathBuf {
+    match env(var).filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => home(env).unwrap_or_default().join(fallback),
+    }
+}
+
+pub fn data_dir(env: &dyn Fn(&str) -> Option<String>) -> PathBuf {
+    xdg_dir(env, "XDG_DATA_HOME", ".local/share").join("qrpg")
+}
+
+pub fn config_dir(env: &dyn Fn(&str) -> Option<String>) -> PathBuf {
+    xdg_dir(env, "XDG_CONFIG_HOME", ".config").join("qrpg")
+}
+
+fn parse_seed(s: &str) -> Result<u64> {
+    s.parse()
+        .map_err(|_| QrpgError::Config(format!("seed {} isn't a number", s)))
+}
+
+impl ConfigFile {
+    /// Reads a config file. A missing file is fine unless it was asked for by name.
+    pub fn load(path: &Path, required: bool) -> Result<Self> {
+        let s = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => {
+                return Ok(Self::default())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut file: ConfigFile = serde_json::from_str(&s).map_err(|e| {
+            QrpgError::Config(format!("config file {} is invalid: {}", path.display(), e))
+        })?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        file.db = file.db.map(|p| base.join(p));
+        file.saves = file.saves.map(|p| base.join(p));
+        Ok(file)
+    }
+}
+
+impl Config {
+    /// Resolves the config from the command line and the process environment.
+    pub fn resolve(args: &[String]) -> Result<Self> {
+        Self::resolve_with(args, &|var| std::env::var(var).ok())
+    }
+
+    /// Resolves the config with `env` standing in for the environment.
+    pub fn resolve_with(args: &[String], env: &dyn Fn(&str) -> Option<String>) -> Result<Self> {
+        let setting = |flag: &str, var: &str| flag_value(args, flag).or_else(|| env(var));
+
+        let file = match setting("--config", "QRPG_CONFIG") {
+            Some(path) => ConfigFile::load(Path::new(&path), true)?,
+            None => ConfigFile::load(&config_dir(env).join("config.json"), false)?,
+        };
+        let data = data_dir(env);
+
+        let db_path = setting("--db", "QRPG_DB")
+            .map(PathBuf::from)
+            .or(file.db)
+            .unwrap_or_else(|| data.join("qrpg.db"));
+        let save_dir = setting("--saves", "QRPG_SAVE_DIR")
+            .map(PathBuf::from)
+            .or(file.saves)
+            .unwrap_or_else(|| data.join(PLAYERS));
+        let store = setting("--store", "QRPG_STORE")
+            .or(file.store)
+            .unwrap_or_else(|| "json".into());
+        if store != "json" && store != "sqlite" {
+            return Err(QrpgError::Config(format!(
+                "unknown save store {}, expected json or sqlite",
+                store
+            )));
+        }
+        let seed = match setting("--seed", "QRPG_SEED") {
+            Some(s) => Some(parse_seed(&s)?),
+            None => file.seed,
+        };
+        let hot_reload = args.iter().any(|a| a == "--hot-reload")
+            || match env("QRPG_HOT_RELOAD") {
+                Some(v) => v == "1" || v == "true",
+                None => file.hot_reload.unwrap_or(false),
+            };
+        Ok(Self {
+            db_path,
+            save_dir,
+            store,
+            seed,
+            hot_reload,
+        })
+    }
+}
+
+mod test {
+    #[cfg(test)]
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |var| vars.iter().find(|(k, _)| k == var).map(|(_, v)| v.clone())
+    }
+
+    #[cfg(test)]
+    fn args(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn defaults_to_xdg_data_dir() {
+        use crate::config::Config;
+        use std::path::PathBuf;
+        let c = Config::resolve_with(&[], &env(&[("HOME", "/home/q")])).unwrap();
+        assert_eq!(
+            c.db_path,
+            PathBuf::from("/home/q/.local/share/qrpg/qrpg.db")
+        );
+        assert_eq!(
+            c.save_dir,
+            PathBuf::from("/home/q/.local/share/qrpg/Players")
+        );
+        assert_eq!(c.store, "json");
+        assert!(!c.hot_reload);
+
+        let c = Config::resolve_with(
+            &[],
+            &env(&[("HOME", "/home/q"), ("XDG_DATA_HOME", "/data")]),
+        )
+        .unwrap();
+        assert_eq!(c.db_path, PathBuf::from("/data/qrpg/qrpg.db"));
+    }
+
+    #[test]
+    fn flags_beat_env_beat_config_file() {
+        use crate::config::Config;
+        use crate::store::test_dir;
+        use std::path::PathBuf;
+        let dir = test_dir("config");
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{"db": "content/qrpg.db", "saves": "/srv/saves", "store": "sqlite", "seed": 5}"#,
+        )
+        .unwrap();
+        let config = dir.join("config.json").display().to_string();
+
+        let e = env(&[("QRPG_CONFIG", &config), ("QRPG_SAVE_DIR", "/env/saves")]);
+        let c = Config::resolve_with(&args(&["--seed", "9"]), &e).unwrap();
+        assert_eq!(c.db_path, dir.join("content/qrpg.db"));
+        assert_eq!(c.save_dir, PathBuf::from("/env/saves"));
+        assert_eq!(c.store, "sqlite");
+        assert_eq!(c.seed, Some(9));
+        assert!(!c.hot_reload);
+
+        let c = Config::resolve_with(&args(&["--saves", "here", "--hot-reload"]), &e).unwrap();
+        assert!(c.hot_reload);
+        assert_eq!(c.save_dir, PathBuf::from("here"));
+        assert_eq!(c.seed, Some(5));
+    }
+
+    #[test]
+    fn subcommands_can_come_after_flags() {
+        use crate::config::positional;
+        assert_eq!(
+            positional(&args(&["--db", "x.db", "validate-content", "--hot-reload"])),
+            args(&["validate-content"])
+        );
+        assert_eq!(
+            positional(&args(&["content", "--seed", "1", "export", "out"])),
+            args(&["content", "export", "out"])
+        );
+        assert!(positional(&args(&["--store", "json"])).is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_settings() {
+        use crate::config::Config;
+        let e = env(&[("HOME", "/home/q")]);
+        assert!(Config::resolve_with(&args(&["--store", "floppy"]), &e).is_err());
+        assert!(Config::resolve_with(&args(&["--seed", "abc"]), &e).is_err());
+        assert!(Config::resolve_with(&args(&["--config", "/nope/config.json"]), &e).is_err());
+    }
+}
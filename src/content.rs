@@ -0,0 +1,203 @@
+use crate::error::{QrpgError, Result};
+use crate::schema::{current_version, migrate, CONTENT_TABLES};
+use serde::Deserialize;
+use sqlite::{Connection, State, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+/// The content tables as text, so they can be reviewed and diffed in git.
+///
+/// Each table becomes `<dir>/<table>.json` holding its column names and one
+/// row per line, in `rowid` order. Importing those files and exporting again
+/// gives back exactly the same text.
+#[derive(Debug, Deserialize)]
+struct TableFile {
+    columns: Vec<String>,
+    rows: Vec<Vec<serde_json::Value>>,
+}
+
+fn to_json(value: Value) -> Result<serde_json::Value> {
+    Ok(match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Integer(i) => i.into(),
+        Value::Float(f) => serde_json::Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| QrpgError::Content(format!("{} can't be written as text", f)))?,
+        Value::String(s) => s.into(),
+        Value::Binary(_) => {
+            return Err(QrpgError::Content(
+                "binary values can't be written as text".into(),
+            ))
+        }
+    })
+}
+
+fn from_json(value: &serde_json::Value) -> Result<Value> {
+    Ok(match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::String(s.clone()),
+        other => {
+            return Err(QrpgError::Content(format!(
+                "{} isn't a value a table can hold",
+                other
+            )))
+        }
+    })
+}
+
+/// Writes one table as text, returning it and how many rows it has.
+fn export_table(db: &Connection, table: &str) -> Result<(String, usize)> {
+    let mut statement = db.prepare(format!("SELECT * FROM \"{}\" ORDER BY rowid", table))?;
+    let columns: Vec<String> = statement.names().iter().map(|n| n.to_string()).collect();
+    let mut rows = Vec::new();
+    while let State::Row = statement.next()? {
+        let mut row = Vec::new();
+        for i in 0..columns.len() {
+            row.push(to_json(statement.read::<Value>(i)?)?);
+        }
+        rows.push(format!("    {}", serde_json::to_string(&row)?));
+    }
+    let mut text = format!(
+        "{{\n  \"columns\": {},\n  \"rows\": [\n",
+        serde_json::to_string(&columns)?
+    );
+    if !rows.is_empty() {
+        text.push_str(&rows.join(",\n"));
+        text.push('\n');
+    }
+    text.push_str("  ]\n}\n");
+    Ok((text, rows.len()))
+}
+
+/// A hash of the schema version and every content table, to tell whether the
+/// content changed and not just something else in the same file, like saves.
+pub fn fingerprint(db: &Connection) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    current_version(db)?.hash(&mut hasher);
+    for table in CONTENT_TABLES.iter() {
+        export_table(db, table)?.0.hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+/// Writes every content table into `dir`, returning how many rows were written.
+pub fn export_content<P: AsRef<Path>>(db: &Connection, dir: P) -> Result<usize> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut n = 0;
+    for table in CONTENT_TABLES.iter() {
+        let (text, rows) = export_table(db, table)?;
+        n += rows;
+        std::fs::write(dir.join(format!("{}.json", table)), text)?;
+    }
+    Ok(n)
+}
+
+fn import_table(db: &Connection, table: &str, file: &TableFile) -> Result<()> {
+    db.execute(format!("DELETE FROM \"{}\"", table))?;
+    let columns: Vec<String> = file.columns.iter().map(|c| format!("\"{}\"", c)).collect();
+    let params = vec!["?"; columns.len()].join(", ");
+    let mut statement = db.prepare(format!(
+        "INSERT INTO \"{}\" ({}) VALUES ({})",
+        table,
+        columns.join(", "),
+        params
+    ))?;
+    for (n, row) in file.rows.iter().enumerate() {
+        if row.len() != columns.len() {
+            return Err(QrpgError::Content(format!(
+                "{} row {} has {} values, but there are {} columns",
+                table,
+                n + 1,
+                row.len(),
+                columns.len()
+            )));
+        }
+        statement.reset()?;
+        for (i, value) in row.iter().enumerate() {
+            statement.bind(i + 1, &from_json(value)?)?;
+        }
+        statement.next()?;
+    }
+    Ok(())
+}
+
+/// Replaces every content table in `db` with the files in `dir`.
+///
+/// The schema comes from the migrations, so `db` can be a new, empty file.
+/// Other tables, like saves kept by the SQLite store, are left alone, and
+/// nothing changes unless every table imports cleanly.
+pub fn import_content<P: AsRef<Path>>(db: &Connection, dir: P) -> Result<usize> {
+    let dir = dir.as_ref();
+    migrate(db)?;
+    let mut files = Vec::new();
+    for table in CONTENT_TABLES.iter() {
+        let path = dir.join(format!("{}.json", table));
+        let text = std::fs::read_to_string(&path)?;
+        let file: TableFile = serde_json::from_str(&text)
+            .map_err(|e| QrpgError::Content(format!("{} is invalid: {}", path.display(), e)))?;
+        files.push((table, file));
+    }
+    db.execute("BEGIN")?;
+    for (table, file) in files.iter() {
+        if let Err(e) = import_table(db, table, file) {
+            db.execute("ROLLBACK")?;
+            return Err(e);
+        }
+    }
+    db.execute("COMMIT")?;
+    Ok(files.iter().map(|(_, f)| f.rows.len()).sum())
+}
+
+mod test {
+    #[test]
+    fn round_trips_exactly() {
+        use crate::content::{export_content, import_content};
+        use crate::store::test_dir;
+        let dir = test_dir("content");
+        let rows = export_content(&crate::persistence::test_db(), dir.join("a")).unwrap();
+
+        let db = sqlite::open(dir.join("rebuilt.db")).unwrap();
+        assert_eq!(import_content(&db, dir.join("a")).unwrap(), rows);
+        export_content(&db, dir.join("b")).unwrap();
+        for table in crate::schema::CONTENT_TABLES.iter() {
+            let file = format!("{}.json", table);
+            assert_eq!(
+                std::fs::read_to_string(dir.join("a").join(&file)).unwrap(),
+                std::fs::read_to_string(dir.join("b").join(&file)).unwrap()
+            );
+        }
+        assert_eq!(
+            format!("{:?}", crate::weapons_from_db(&db).unwrap()),
+            format!(
+                "{:?}",
+                crate::weapons_from_db(&crate::persistence::test_db()).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn checked_in_content_matches_the_database() {
+        use crate::content::export_content;
+        use crate::store::test_dir;
+        let dir = test_dir("content-git");
+        export_content(&crate::persistence::test_db(), &dir).unwrap();
+        for table in crate::schema::CONTENT_TABLES.iter() {
+            let file = format!("{}.json", table);
+            let checked_in = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("content")
+                .join(&file);
+            assert_eq!(
+                std::fs::read_to_string(checked_in).unwrap(),
+                std::fs::read_to_string(dir.join(&file)).unwrap(),
+                "content/{} is out of date, run `qrpg content export`",
+                file
+            );
+        }
+    }
+}
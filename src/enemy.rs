@@ -0,0 +1,212 @@
+use crate::combat::{Attacker, Combatant, Defender};
+use crate::items::{Effect, Item, Weapon};
+use crate::error::{QrpgError, Result};
+use crate::registry::ContentRegistry;
+use crate::rng::GameRng;
+use crate::stats::Stats;
+use crate::status::{Status, StatusKind, Statuses};
+use rand::prelude::*;
+
+#[derive(Default, Debug, Clone)]
+pub struct Enemy {
+    pub name: String,
+    pub stats: Stats,
+    pub health: i32,
+    pub weapon: Option<Weapon>,
+    /// The player levels this enemy shows up at.
+    pub min_level: i32,
+    pub max_level: i32,
+    /// Experience for beating it.
+    pub xp: i32,
+    /// Money dropped when it's beaten.
+    pub gold: i32,
+    /// The name of its loot table in `loot_tables`, if it drops anything.
+    pub loot: Option<String>,
+    /// ASCII art shown on the battle screen.
+    pub portrait: String,
+    /// What the battle screen says about it, like `Bunny is about to strike!`.
+    pub intro: String,
+    /// Statuses it can't be given.
+    pub immune: Vec<StatusKind>,
+    /// A status effect its attacks can put on whoever they hit.
+    pub inflicts: Option<Effect>,
+    /// Statuses from the current battle.
+    pub statuses: Statuses,
+}
+
+/// One row of a loot table: something an enemy might drop, and how likely it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LootDrop {
+    /// The name of a weapon or item.
+    pub item: String,
+    /// From 0 to 1.
+    pub chance: f64,
+}
+
+impl Enemy {
+    pub fn random(content: &ContentRegistry, rng: &mut GameRng) -> Result<Self> {
+        let enemies = content.enemies();
+        if enemies.is_empty() {
+            return Err(QrpgError::Content("the enemies table is empty".into()));
+        }
+        let n: usize = rng.gen_range(0, enemies.len());
+        Ok(enemies[n].clone())
+    }
+
+    /// A random enemy whose level range includes `level`, or any enemy if none do.
+    pub fn random_for_level(
+        content: &ContentRegistry,
+        level: i32,
+        rng: &mut GameRng,
+    ) -> Result<Self> {
+        let fitting: Vec<&Enemy> = content
+            .enemies()
+            .iter()
+            .filter(|e| e.min_level <= level && level <= e.max_level)
+            .collect();
+        if fitting.is_empty() {
+            return Self::random(content, rng);
+        }
+        let n: usize = rng.gen_range(0, fitting.len());
+        Ok(fitting[n].clone())
+    }
+
+    pub fn with_stats(&self, p: i32, t: i32, m: i32) -> Self {
+        let s = Stats {
+            physique: p,
+            technique: t,
+            mystique: m,
+        };
+        Enemy {
+            stats: s,
+            health: s.max_health(),
+            weapon: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_weapon<T: Into<f32>>(
+        &self,
+        weapon: &str,
+        p: T,
+        t: T,
+        m: T,
+        weight: T,
+        value: i32,
+    ) -> Self {
+        Enemy {
+            weapon: Some(Weapon {
+                physique_scale: p.into(),
+                technique_scale: t.into(),
+                mystique_scale: m.into(),
+                item: Item::new(weapon, weight.into(), value),
+            }),
+            ..self.clone()
+        }
+    }
+
+    pub fn new<T: AsRef<str>>(name: T) -> Self {
+        Enemy {
+            name: name.as_ref().into(),
+            stats: Default::default(),
+            health: 100,
+            weapon: None,
+            min_level: 1,
+            max_level: 1,
+            xp: 0,
+            gold: 0,
+            loot: None,
+            portrait: String::new(),
+            intro: String::new(),
+            immune: Vec::new(),
+            inflicts: None,
+            statuses: Statuses::new(),
+        }
+    }
+
+    /// The enemy's intro line, or a plain one if content doesn't give it one.
+    pub fn intro(&self) -> String {
+        if self.intro.is_empty() {
+            format!("{} is about to strike!", self.name)
+        } else {
+            self.intro.clone()
+        }
+    }
+}
+
+impl std::fmt::Display for Enemy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&format!(
+            "Enemy{{ name: {}, stats:{} }}",
+            self.name, self.stats
+        ))
+    }
+}
+
+impl Combatant for Enemy {
+    fn get_stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Attacker for Enemy {
+    /// Its weapon's damage, or its physique if it's unarmed.
+    fn damage(&self) -> i32 {
+        let damage = match &self.weapon {
+            Some(w) => w.damage(self),
+            None => self.get_stats().physique,
+        };
+        (damage - self.statuses.attack_penalty()).max(0)
+    }
+}
+
+impl Defender for Enemy {
+    fn defense(&self) -> i32 {
+        (self.name.len() / 2usize) as i32 + self.statuses.defense_bonus()
+    }
+
+    fn take_damage(&mut self, damage: i32) {
+        self.health -= damage;
+    }
+
+    fn afflict(&mut self, status: Status) -> bool {
+        if self.immune.contains(&status.kind) {
+            return false;
+        }
+        self.statuses.add(status);
+        true
+    }
+}
+
+mod test {
+    #[test]
+    fn test2() {
+        let e = crate::Enemy::random(
+            &crate::registry::test_content(),
+            &mut crate::GameRng::default(),
+        )
+        .unwrap();
+        println!("{}", e);
+    }
+
+    #[test]
+    fn test5() {
+        use crate::{Attacker, Enemy};
+        let enemy = Enemy::new("Rabbit").with_stats(10, 5, 6);
+        let damage: i32 = enemy.damage();
+        println!("{}, damage: {}", enemy, damage);
+    }
+
+    #[test]
+    fn armed_enemies_hit_harder() {
+        use crate::{Attacker, Enemy};
+        let unarmed = Enemy::new("Orc").with_stats(5, 5, 1);
+        assert_eq!(unarmed.damage(), 5);
+        let armed = unarmed.with_weapon("Axe", 1.5, 0.5, 0.0, 5.0, 10);
+        assert_eq!(armed.damage(), 10);
+    }
+}
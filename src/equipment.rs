@@ -0,0 +1,131 @@
+use crate::items::{Contents, Weapon};
+use serde::{Deserialize, Serialize};
+
+/// Where something is worn or held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipSlot {
+    MainHand,
+    OffHand,
+    Head,
+    Body,
+    Legs,
+    Accessory,
+}
+
+impl EquipSlot {
+    /// The slot as it's written in the items table.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "main_hand" => EquipSlot::MainHand,
+            "off_hand" => EquipSlot::OffHand,
+            "head" => EquipSlot::Head,
+            "body" => EquipSlot::Body,
+            "legs" => EquipSlot::Legs,
+            "accessory" => EquipSlot::Accessory,
+            _ => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for EquipSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EquipSlot::MainHand => "Main hand",
+            EquipSlot::OffHand => "Off hand",
+            EquipSlot::Head => "Head",
+            EquipSlot::Body => "Body",
+            EquipSlot::Legs => "Legs",
+            EquipSlot::Accessory => "Accessory",
+        })
+    }
+}
+
+/// The slots a player has, in the order the equipment screen shows them.
+/// There's room for two accessories.
+pub const SLOTS: [EquipSlot; 7] = [
+    EquipSlot::MainHand,
+    EquipSlot::OffHand,
+    EquipSlot::Head,
+    EquipSlot::Body,
+    EquipSlot::Legs,
+    EquipSlot::Accessory,
+    EquipSlot::Accessory,
+];
+
+/// What the player is wearing and holding, one thing per slot in `SLOTS`.
+///
+/// Slots are addressed by their index in `SLOTS`. Saved as a list with an
+/// entry, possibly null, for each.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Equipment {
+    worn: [Option<Contents>; 7],
+}
+
+impl Equipment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&Contents> {
+        self.worn.get(slot).and_then(Option::as_ref)
+    }
+
+    /// The weapon in the main hand, if there is one.
+    pub fn weapon(&self) -> Option<&Weapon> {
+        match self.get(0) {
+            Some(Contents::Weapon(w)) => Some(w),
+            _ => None,
+        }
+    }
+
+    /// Whether `contents` can go in the slot at `slot`.
+    pub fn fits(slot: usize, contents: &Contents) -> bool {
+        SLOTS.get(slot).is_some() && contents.slot() == Some(SLOTS[slot])
+    }
+
+    /// Puts `contents` in the slot at `slot`, handing back what was there.
+    /// Something that doesn't fit is handed straight back and nothing changes.
+    pub fn equip(&mut self, slot: usize, contents: Contents) -> Option<Contents> {
+        if !Self::fits(slot, &contents) {
+            return Some(contents);
+        }
+        self.worn[slot].replace(contents)
+    }
+
+    /// Empties the slot at `slot`, returning what was in it.
+    pub fn unequip(&mut self, slot: usize) -> Option<Contents> {
+        self.worn.get_mut(slot).and_then(Option::take)
+    }
+
+    /// The total defense of everything worn.
+    pub fn defense(&self) -> i32 {
+        self.worn.iter().flatten().map(|c| c.item().defense).sum()
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.worn.iter().flatten().map(|c| c.item().weight).sum()
+    }
+}
+
+mod test {
+    #[test]
+    fn things_only_go_where_they_fit() {
+        use crate::equipment::Equipment;
+        use crate::Contents;
+        let content = crate::registry::test_content();
+        let helm = Contents::Item(content.item("Leather Cap").unwrap().clone());
+        let sword = Contents::Weapon(content.weapon("Sword").unwrap().clone());
+        let mut gear = Equipment::new();
+        assert_eq!(gear.equip(0, helm.clone()), Some(helm.clone()));
+        assert!(gear.get(0).is_none());
+        assert_eq!(gear.equip(2, helm.clone()), None);
+        assert_eq!(gear.equip(0, sword), None);
+        assert_eq!(gear.weapon().unwrap().name(), "Sword");
+        assert_eq!(gear.defense(), helm.item().defense);
+
+        assert_eq!(gear.unequip(2), Some(helm));
+        assert_eq!(gear.defense(), 0);
+    }
+}
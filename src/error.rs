@@ -0,0 +1,65 @@
+use std::fmt;
+
+/// Everything that can go wrong loading or saving game data.
+#[derive(Debug)]
+pub enum QrpgError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Sqlite(sqlite::Error),
+    /// The game data loaded fine but doesn't make sense, e.g. a weapon without a weight.
+    Content(String),
+    /// A save that isn't valid JSON, or whose checksum doesn't match its contents.
+    CorruptSave(String),
+    /// A character name that can't be used, and why.
+    InvalidName(String),
+    /// Bad command line arguments or settings.
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, QrpgError>;
+
+impl fmt::Display for QrpgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QrpgError::Io(e) => write!(f, "Couldn't read or write game files: {}", e),
+            QrpgError::Json(e) => write!(f, "Couldn't read or write JSON: {}", e),
+            QrpgError::Sqlite(e) => write!(f, "The game database couldn't be read: {}", e),
+            QrpgError::Content(msg) => write!(f, "The game data is invalid: {}", msg),
+            QrpgError::CorruptSave(msg) => write!(f, "The save file is corrupted: {}", msg),
+            QrpgError::InvalidName(msg) => write!(f, "That name can't be used: {}", msg),
+            QrpgError::Config(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for QrpgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QrpgError::Io(e) => Some(e),
+            QrpgError::Json(e) => Some(e),
+            QrpgError::Sqlite(e) => Some(e),
+            QrpgError::Content(_)
+            | QrpgError::CorruptSave(_)
+            | QrpgError::InvalidName(_)
+            | QrpgError::Config(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for QrpgError {
+    fn from(e: std::io::Error) -> Self {
+        QrpgError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for QrpgError {
+    fn from(e: serde_json::Error) -> Self {
+        QrpgError::Json(e)
+    }
+}
+
+impl From<sqlite::Error> for QrpgError {
+    fn from(e: sqlite::Error) -> Self {
+        QrpgError::Sqlite(e)
+    }
+}
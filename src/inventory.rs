@@ -0,0 +1,250 @@
+use crate::items::{Contents, ItemCategory};
+use serde::{Deserialize, Serialize};
+
+/// Some number of the same thing, taking up one inventory slot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stack {
+    pub contents: Contents,
+    pub quantity: u32,
+}
+
+impl Stack {
+    /// How many of this thing fit in one stack.
+    pub fn max(&self) -> u32 {
+        self.contents.item().max_stack.max(1)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.quantity >= self.max()
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.contents.item().weight * self.quantity as f32
+    }
+}
+
+impl std::fmt::Display for Stack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.quantity == 1 {
+            write!(f, "{}", self.contents)
+        } else {
+            write!(f, "{} x{}", self.contents, self.quantity)
+        }
+    }
+}
+
+/// The player's things, kept in stacks of up to each item's `max_stack`.
+///
+/// Stacks are addressed by their index, which is what the menus hand back.
+/// Saved as a plain list of stacks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Inventory {
+    stacks: Vec<Stack>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stacks(&self) -> &[Stack] {
+        &self.stacks
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Stack> {
+        self.stacks.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.stacks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    /// The weight of everything in every stack.
+    pub fn weight(&self) -> f32 {
+        self.stacks.iter().map(Stack::weight).sum()
+    }
+
+    /// How many of the item called `name` there are across every stack.
+    pub fn count(&self, name: &str) -> u32 {
+        self.stacks
+            .iter()
+            .filter(|s| s.contents.item().name == name)
+            .map(|s| s.quantity)
+            .sum()
+    }
+
+    /// Adds `quantity` of something, topping up stacks of it that aren't full
+    /// before starting new ones.
+    pub fn add(&mut self, contents: Contents, quantity: u32) {
+        let mut left = quantity;
+        for stack in self.stacks.iter_mut() {
+            if left == 0 {
+                return;
+            }
+            if stack.contents == contents && !stack.is_full() {
+                let n = left.min(stack.max() - stack.quantity);
+                stack.quantity += n;
+                left -= n;
+            }
+        }
+        while left > 0 {
+            let mut stack = Stack {
+                contents: contents.clone(),
+                quantity: 0,
+            };
+            stack.quantity = left.min(stack.max());
+            left -= stack.quantity;
+            self.stacks.push(stack);
+        }
+    }
+
+    /// Takes up to `quantity` from the stack at `index`, dropping the stack if
+    /// it's emptied. Returns what was taken.
+    pub fn remove(&mut self, index: usize, quantity: u32) -> Option<Stack> {
+        let stack = self.stacks.get_mut(index)?;
+        let n = quantity.min(stack.quantity);
+        stack.quantity -= n;
+        let taken = Stack {
+            contents: stack.contents.clone(),
+            quantity: n,
+        };
+        if stack.quantity == 0 {
+            self.stacks.remove(index);
+        }
+        Some(taken)
+    }
+
+    /// Takes a single thing from the stack at `index`.
+    pub fn take_one(&mut self, index: usize) -> Option<Contents> {
+        self.remove(index, 1).map(|s| s.contents)
+    }
+
+    /// Moves `quantity` out of the stack at `index` into a new stack right
+    /// after it. Does nothing and returns false unless both halves would be non-empty.
+    pub fn split(&mut self, index: usize, quantity: u32) -> bool {
+        let stack = match self.stacks.get_mut(index) {
+            Some(stack) if quantity > 0 && quantity < stack.quantity => stack,
+            _ => return false,
+        };
+        stack.quantity -= quantity;
+        let new = Stack {
+            contents: stack.contents.clone(),
+            quantity,
+        };
+        self.stacks.insert(index + 1, new);
+        true
+    }
+
+    /// Combines partly filled stacks of the same thing into as few stacks as will hold them.
+    pub fn merge(&mut self) {
+        let stacks = std::mem::take(&mut self.stacks);
+        for stack in stacks {
+            self.add(stack.contents, stack.quantity);
+        }
+    }
+
+    /// Sorts stacks by category, then by name.
+    pub fn sort_by_category(&mut self) {
+        self.stacks.sort_by(|a, b| {
+            (a.contents.category(), &a.contents.item().name)
+                .cmp(&(b.contents.category(), &b.contents.item().name))
+        });
+    }
+
+    /// The indexes of the stacks in `category`, or of every stack if it's None.
+    pub fn filter(&self, category: Option<ItemCategory>) -> Vec<usize> {
+        self.stacks
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| category.is_none_or(|c| s.contents.category() == c))
+            .map(|(n, _)| n)
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Inventory {
+    /// A short list of names and quantities, like `Sword, Health Potion x3`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.stacks.is_empty() {
+            return f.write_str("None");
+        }
+        let names: Vec<String> = self
+            .stacks
+            .iter()
+            .map(|s| match s.quantity {
+                1 => s.contents.item().name.clone(),
+                n => format!("{} x{}", s.contents.item().name, n),
+            })
+            .collect();
+        f.write_str(&names.join(", "))
+    }
+}
+
+mod test {
+    #[cfg(test)]
+    fn potion(max_stack: u32) -> crate::Contents {
+        crate::Contents::Item(crate::Item {
+            max_stack,
+            category: crate::ItemCategory::Consumable,
+            ..crate::Item::new("Potion", 0.5, 10)
+        })
+    }
+
+    #[test]
+    fn adding_fills_stacks_up_to_their_max() {
+        use crate::inventory::Inventory;
+        let mut inv = Inventory::new();
+        inv.add(potion(10), 4);
+        inv.add(potion(10), 9);
+        assert_eq!(inv.len(), 2);
+        assert_eq!(inv.get(0).unwrap().quantity, 10);
+        assert_eq!(inv.get(1).unwrap().quantity, 3);
+        assert_eq!(inv.count("Potion"), 13);
+        assert_eq!(inv.weight(), 6.5);
+        assert_eq!(inv.to_string(), "Potion x10, Potion x3");
+
+        inv.remove(0, 10);
+        assert_eq!(inv.len(), 1);
+        assert_eq!(inv.take_one(0), Some(potion(10)));
+        assert_eq!(inv.count("Potion"), 2);
+    }
+
+    #[test]
+    fn split_and_merge() {
+        use crate::inventory::Inventory;
+        let mut inv = Inventory::new();
+        inv.add(potion(10), 6);
+        assert!(!inv.split(0, 6));
+        assert!(inv.split(0, 2));
+        assert!(inv.split(0, 2));
+        assert_eq!(inv.len(), 3);
+        inv.merge();
+        assert_eq!(inv.len(), 1);
+        assert_eq!(inv.get(0).unwrap().quantity, 6);
+    }
+
+    #[test]
+    fn sorts_and_filters_by_category() {
+        use crate::inventory::Inventory;
+        use crate::{Contents, Item, ItemCategory};
+        let content = crate::registry::test_content();
+        let mut inv = Inventory::new();
+        inv.add(Contents::Item(Item::new("Rock", 1.0, 1)), 1);
+        inv.add(potion(10), 2);
+        inv.add(Contents::Weapon(content.weapon("Axe").unwrap().clone()), 1);
+        inv.sort_by_category();
+        let names: Vec<&str> = inv
+            .stacks()
+            .iter()
+            .map(|s| s.contents.item().name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Axe", "Potion", "Rock"]);
+        assert_eq!(inv.filter(Some(ItemCategory::Consumable)), vec![1]);
+        assert_eq!(inv.filter(None).len(), 3);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::combat::Attacker;
+use crate::equipment::EquipSlot;
+use crate::stats::{Stat, Stats};
+use crate::status::StatusKind;
+use serde::{Deserialize, Serialize};
+
+/// What kind of thing an item is, for the shop and the inventory.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemCategory {
+    Weapon,
+    Armor,
+    Consumable,
+    #[default]
+    Material,
+    Quest,
+    Key,
+}
+
+impl ItemCategory {
+    /// The category as it's written in the items table.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "weapon" => ItemCategory::Weapon,
+            "armor" => ItemCategory::Armor,
+            "consumable" => ItemCategory::Consumable,
+            "material" => ItemCategory::Material,
+            "quest" => ItemCategory::Quest,
+            "key" => ItemCategory::Key,
+            _ => return None,
+        })
+    }
+
+    /// Whether the shop deals in this kind of item.
+    pub fn for_sale(&self) -> bool {
+        matches!(
+            self,
+            ItemCategory::Weapon
+                | ItemCategory::Armor
+                | ItemCategory::Consumable
+                | ItemCategory::Material
+        )
+    }
+}
+
+impl std::fmt::Display for ItemCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ItemCategory::Weapon => "Weapon",
+            ItemCategory::Armor => "Armor",
+            ItemCategory::Consumable => "Consumable",
+            ItemCategory::Material => "Material",
+            ItemCategory::Quest => "Quest item",
+            ItemCategory::Key => "Key",
+        })
+    }
+}
+
+/// What an effect does when it's used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EffectKind {
+    /// Restores health.
+    Heal,
+    /// Restores stamina.
+    Stamina,
+    /// Restores mana.
+    Mana,
+    /// Raises a stat until the end of the battle.
+    Buff,
+    /// Hurts the enemy, like a thrown bomb.
+    Damage,
+    /// Hurts every enemy at once.
+    Blast,
+    /// Puts a status on the enemy, or on the user if it's a helpful one.
+    Status,
+}
+
+impl EffectKind {
+    /// The kind as it's written in the effects table.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "heal" => EffectKind::Heal,
+            "stamina" => EffectKind::Stamina,
+            "mana" => EffectKind::Mana,
+            "buff" => EffectKind::Buff,
+            "damage" => EffectKind::Damage,
+            "blast" => EffectKind::Blast,
+            "status" => EffectKind::Status,
+            _ => return None,
+        })
+    }
+}
+
+/// A row of the effects table, which items refer to by name.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Effect {
+    pub name: String,
+    pub kind: EffectKind,
+    pub amount: i32,
+    /// The stat a buff raises.
+    #[serde(default)]
+    pub stat: Option<Stat>,
+    /// The status a status effect puts on, with `amount` as its potency.
+    #[serde(default)]
+    pub status: Option<StatusKind>,
+    /// How many turns the status lasts.
+    #[serde(default)]
+    pub turns: i32,
+}
+
+impl Effect {
+    /// Whether the effect only makes sense with an enemy around.
+    pub fn battle_only(&self) -> bool {
+        matches!(
+            self.kind,
+            EffectKind::Buff | EffectKind::Damage | EffectKind::Blast | EffectKind::Status
+        )
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Default, Debug, PartialOrd, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Item {
+    pub name: String,
+    pub weight: f32,
+    pub value: i32,
+    // Saves from before the items table don't have these.
+    #[serde(default)]
+    pub category: ItemCategory,
+    /// How many fit in one inventory stack.
+    #[serde(default = "one")]
+    pub max_stack: u32,
+    /// What using the item does, if it can be used.
+    #[serde(default)]
+    pub effect: Option<Effect>,
+    #[serde(default)]
+    pub description: String,
+    /// Where it's worn, for armor.
+    #[serde(default)]
+    pub slot: Option<EquipSlot>,
+    /// How much damage it stops while it's worn.
+    #[serde(default)]
+    pub defense: i32,
+    /// The stats needed to equip it.
+    #[serde(default)]
+    pub requires: Stats,
+}
+
+impl Item {
+    pub fn new(name: &str, weight: f32, value: i32) -> Self {
+        let name = name.to_owned();
+        Self {
+            name,
+            weight,
+            value,
+            max_stack: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the item does something when it's used from the inventory.
+    pub fn usable(&self) -> bool {
+        self.category == ItemCategory::Consumable && self.effect.is_some()
+    }
+}
+
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
+pub struct Weapon {
+    pub item: Item,
+    pub physique_scale: f32,
+    pub technique_scale: f32,
+    pub mystique_scale: f32,
+}
+
+impl Weapon {
+    pub fn name(&self) -> &str {
+        &self.item.name
+    }
+
+    pub fn damage(&self, attacker: &dyn Attacker) -> i32 {
+        ((self.physique_scale * attacker.get_stats().physique as f32)
+            + (self.technique_scale * attacker.get_stats().technique as f32)
+            + (self.mystique_scale * attacker.get_stats().mystique as f32)) as i32
+    }
+}
+
+impl std::fmt::Display for Weapon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&format!(
+            "{}, Physique: {}, Technique: {}, Mystique: {}, Value: {}, Weight: {}",
+            self.item,
+            self.physique_scale,
+            self.technique_scale,
+            self.mystique_scale,
+            self.item.value,
+            self.item.weight,
+        ))
+    }
+}
+
+#[derive(Clone, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
+pub enum Contents {
+    Item(Item),
+    Weapon(Weapon),
+}
+
+impl Contents {
+    pub fn item(&self) -> &Item {
+        match self {
+            Contents::Item(i) => i,
+            Contents::Weapon(w) => &w.item,
+        }
+    }
+
+    pub fn category(&self) -> ItemCategory {
+        match self {
+            Contents::Item(i) => i.category,
+            Contents::Weapon(_) => ItemCategory::Weapon,
+        }
+    }
+
+    /// Where it can be equipped, if anywhere. Weapons always go in the main hand.
+    pub fn slot(&self) -> Option<EquipSlot> {
+        match self {
+            Contents::Item(i) => i.slot,
+            Contents::Weapon(_) => Some(EquipSlot::MainHand),
+        }
+    }
+}
+
+impl std::fmt::Display for Contents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let fmt = match self {
+            Contents::Weapon(w) => format!("{}", w),
+            Contents::Item(i) if i.category == ItemCategory::Armor => format!(
+                "{}, {}, Defense: {}, Value: {}, Weight: {}",
+                i,
+                i.slot.map(|s| s.to_string()).unwrap_or_default(),
+                i.defense,
+                i.value,
+                i.weight
+            ),
+            Contents::Item(i) => format!(
+                "{}, {}, Value: {}, Weight: {}",
+                i, i.category, i.value, i.weight
+            ),
+        };
+        f.write_str(&fmt)
+    }
+}
@@ -0,0 +1,54 @@
+//! Quincy RPG as a library.
+//!
+//! The domain types (stats, items, players, enemies) and the game flows built
+//! on top of them live here so other binaries, tests and bots can use them.
+//! The `qrpg` binary only wires the main menu together.
+
+pub mod battle;
+pub mod combat;
+pub mod config;
+pub mod content;
+pub mod enemy;
+pub mod equipment;
+pub mod error;
+pub mod inventory;
+pub mod items;
+pub mod persistence;
+pub mod player;
+pub mod registry;
+pub mod rng;
+pub mod save;
+pub mod schema;
+pub mod shop;
+pub mod skills;
+pub mod sqlite_store;
+pub mod stats;
+pub mod status;
+pub mod store;
+pub mod story;
+pub mod ui;
+pub mod validate;
+
+pub use battle::{Battle, BattleResult, Fighter};
+pub use combat::{area_attack, combat, strike, Attacker, BattleOutcome, Combatant, Defender};
+pub use config::Config;
+pub use enemy::{Enemy, LootDrop};
+pub use equipment::{EquipSlot, Equipment};
+pub use error::{QrpgError, Result};
+pub use inventory::{Inventory, Stack};
+pub use items::{Contents, Effect, EffectKind, Item, ItemCategory, Weapon};
+pub use persistence::{
+    dir_exists, enemies_from_db, get_db, items_from_db, loot_tables_from_db, read_db,
+    skills_from_db, slug, validate_name, validate_new_name, weapons_from_db, PLAYERS,
+};
+pub use player::{equipment, inventory, Player};
+pub use registry::ContentRegistry;
+pub use rng::GameRng;
+pub use save::{SaveFile, SaveMetadata};
+pub use shop::{buy, sell, shop};
+pub use skills::{trainer, Cost, Skill, SkillTarget};
+pub use sqlite_store::SqliteStore;
+pub use stats::{Stat, Stats};
+pub use status::{Status, StatusKind, Statuses};
+pub use store::{migrate_saves, JsonFileStore, LoadedSave, SaveEntry, SaveStore, Slot};
+pub use story::{char_intro, explore, reload_content, story, town};
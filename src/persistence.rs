@@ -0,0 +1,589 @@
+use crate::enemy::{Enemy, LootDrop};
+use crate::equipment::EquipSlot;
+use crate::error::{QrpgError, Result};
+use crate::items::{Effect, EffectKind, Item, ItemCategory, Weapon};
+use crate::schema::migrate;
+use crate::skills::{Cost, Skill, SkillTarget};
+use crate::stats::{Stat, Stats};
+use crate::status::StatusKind;
+use sqlite::{Connection, State};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Opens the content database and brings its schema up to date. A missing
+/// file is an error rather than letting sqlite quietly create an empty database.
+pub fn get_db<P: AsRef<Path>>(path: P) -> Result<Connection> {
+    let db = Connection::open(found(path.as_ref())?)?;
+    migrate(&db)?;
+    Ok(db)
+}
+
+/// Opens the content database as it is, read only and without migrating it,
+/// for looking at a database the game might not be able to upgrade.
+pub fn read_db<P: AsRef<Path>>(path: P) -> Result<Connection> {
+    let flags = sqlite::OpenFlags::new().set_read_only();
+    Ok(Connection::open_with_flags(found(path.as_ref())?, flags)?)
+}
+
+fn found(path: &Path) -> Result<&Path> {
+    if !path.exists() {
+        return Err(QrpgError::Config(format!(
+            "The game database wasn't found at {}. Pass --db or set QRPG_DB to where qrpg.db is.",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+/// The content database that ships with the repo, for tests. It's opened read
+/// only and isn't migrated, so running tests never changes the tracked file.
+#[cfg(test)]
+pub(crate) fn test_db() -> Connection {
+    read_db(concat!(env!("CARGO_MANIFEST_DIR"), "/qrpg.db")).unwrap()
+}
+
+/// The name of the save folder inside the data dir.
+pub const PLAYERS: &str = "Players";
+
+/// Makes sure the save folder exists.
+pub fn dir_exists<P: AsRef<Path>>(dir: P) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+/// Finds a column by name, so content tables can have their columns in any order.
+fn column(statement: &sqlite::Statement, name: &str) -> Result<usize> {
+    statement
+        .names()
+        .iter()
+        .position(|n| *n == name)
+        .ok_or_else(|| QrpgError::Content(format!("there's no {} column", name)))
+}
+
+/// Reads a column that may be NULL.
+fn optional<T: sqlite::Readable>(statement: &sqlite::Statement, name: &str) -> Result<Option<T>> {
+    Ok(statement.read::<Option<T>>(column(statement, name)?)?)
+}
+
+/// Reads a column that the game can't do without, naming the row and column if it's NULL.
+fn required<T: sqlite::Readable>(
+    statement: &sqlite::Statement,
+    name: &str,
+    row: &str,
+) -> Result<T> {
+    match optional(statement, name)? {
+        Some(v) => Ok(v),
+        None => Err(QrpgError::Content(format!(
+            "{} is missing a value for {}",
+            row, name
+        ))),
+    }
+}
+
+/// The `min_` stat columns of a weapon or item, which are 0 when they're
+/// NULL or the table doesn't have them.
+fn requirements(statement: &sqlite::Statement) -> Result<Stats> {
+    let min = |stat: &str| -> Result<i32> {
+        let name = format!("min_{}", stat);
+        if !statement.names().contains(&name.as_str()) {
+            return Ok(0);
+        }
+        Ok(optional::<i64>(statement, &name)?.unwrap_or(0) as i32)
+    };
+    Ok(Stats::new(
+        min("physique")?,
+        min("technique")?,
+        min("mystique")?,
+    ))
+}
+
+pub fn weapons_from_db(db: &Connection) -> Result<Vec<Weapon>> {
+    let mut v = Vec::new();
+    let mut statement = db.prepare("SELECT * FROM weapons")?;
+    while let State::Row = statement.next()? {
+        let name: String = required(&statement, "name", "a weapon")?;
+        let row = format!("weapon {}", name);
+        let weight: f64 = required(&statement, "weight", &row)?;
+        let value: i64 = required(&statement, "value", &row)?;
+        let p: f64 = required(&statement, "physique", &row)?;
+        let t: f64 = required(&statement, "technique", &row)?;
+        let m: f64 = required(&statement, "mystique", &row)?;
+        let w = Weapon {
+            item: Item {
+                category: ItemCategory::Weapon,
+                slot: Some(EquipSlot::MainHand),
+                requires: requirements(&statement)?,
+                ..Item::new(&name, weight as f32, value as i32)
+            },
+            physique_scale: p as f32,
+            technique_scale: t as f32,
+            mystique_scale: m as f32,
+        };
+        v.push(w);
+    }
+    Ok(v)
+}
+
+/// The statuses in a comma separated list, like `poison, bleed`.
+fn immunities(list: &str, row: &str) -> Result<Vec<StatusKind>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            StatusKind::from_name(s).ok_or_else(|| {
+                QrpgError::Content(format!("{} is immune to an unknown status {}", row, s))
+            })
+        })
+        .collect()
+}
+
+/// Every enemy, with its stats, weapon, rewards and the status it inflicts.
+/// An enemy whose weapon isn't in the weapons table is a content error, and
+/// so is one inflicting anything but a status effect.
+pub fn enemies_from_db(db: &Connection) -> Result<Vec<Enemy>> {
+    let weapons = weapons_from_db(db)?;
+    // Only loaded if an enemy inflicts something.
+    let mut effects: Option<Vec<Effect>> = None;
+    let mut v = Vec::new();
+    let mut statement = db.prepare("SELECT * FROM enemies")?;
+    while let State::Row = statement.next()? {
+        let name: String = required(&statement, "name", "an enemy")?;
+        let row = format!("enemy {}", name);
+        let p: i64 = required(&statement, "physique", &row)?;
+        let t: i64 = required(&statement, "technique", &row)?;
+        let m: i64 = required(&statement, "mystique", &row)?;
+        let weapon = match optional::<String>(&statement, "weapon")? {
+            Some(w) => match weapons.iter().find(|weapon| weapon.name() == w) {
+                Some(weapon) => Some(weapon.clone()),
+                None => {
+                    return Err(QrpgError::Content(format!(
+                        "{} has weapon {}, which isn't in the weapons table",
+                        row, w
+                    )))
+                }
+            },
+            None => None,
+        };
+        let min_level: i64 = required(&statement, "min_level", &row)?;
+        let max_level: i64 = required(&statement, "max_level", &row)?;
+        let xp: i64 = required(&statement, "xp", &row)?;
+        let gold: i64 = required(&statement, "gold", &row)?;
+        let inflicts = match optional::<String>(&statement, "inflicts")? {
+            Some(e) => {
+                if effects.is_none() {
+                    effects = Some(effects_from_db(db)?);
+                }
+                let effects = effects.as_deref().unwrap_or_default();
+                match effects.iter().find(|effect| effect.name == e) {
+                    Some(effect) if effect.kind == EffectKind::Status => Some(effect.clone()),
+                    Some(_) => {
+                        return Err(QrpgError::Content(format!(
+                            "{} inflicts {}, which isn't a status effect",
+                            row, e
+                        )))
+                    }
+                    None => {
+                        return Err(QrpgError::Content(format!(
+                            "{} inflicts {}, which isn't in the effects table",
+                            row, e
+                        )))
+                    }
+                }
+            }
+            None => None,
+        };
+        let immune = immunities(
+            &optional::<String>(&statement, "immunities")?.unwrap_or_default(),
+            &row,
+        )?;
+        let stats = Stats::new(p as i32, t as i32, m as i32);
+        v.push(Enemy {
+            name,
+            stats,
+            health: stats.max_health(),
+            weapon,
+            min_level: min_level as i32,
+            max_level: max_level as i32,
+            xp: xp as i32,
+            gold: gold as i32,
+            loot: optional(&statement, "loot")?,
+            portrait: optional(&statement, "portrait")?.unwrap_or_default(),
+            intro: optional(&statement, "intro")?.unwrap_or_default(),
+            immune,
+            inflicts,
+            statuses: Default::default(),
+        });
+    }
+    Ok(v)
+}
+
+/// Every loot table, by name, with its drops in table order.
+pub fn loot_tables_from_db(db: &Connection) -> Result<HashMap<String, Vec<LootDrop>>> {
+    let mut tables: HashMap<String, Vec<LootDrop>> = HashMap::new();
+    let mut statement = db.prepare("SELECT * FROM loot_tables")?;
+    while let State::Row = statement.next()? {
+        let name: String = required(&statement, "name", "a loot table")?;
+        let row = format!("loot table {}", name);
+        let drop = LootDrop {
+            item: required(&statement, "item", &row)?,
+            chance: required(&statement, "chance", &row)?,
+        };
+        tables.entry(name).or_default().push(drop);
+    }
+    Ok(tables)
+}
+
+pub fn effects_from_db(db: &Connection) -> Result<Vec<Effect>> {
+    let mut v = Vec::new();
+    let mut statement = db.prepare("SELECT * FROM effects")?;
+    while let State::Row = statement.next()? {
+        let name: String = required(&statement, "name", "an effect")?;
+        let row = format!("effect {}", name);
+        let kind: String = required(&statement, "kind", &row)?;
+        let kind = EffectKind::from_name(&kind)
+            .ok_or_else(|| QrpgError::Content(format!("{} has an unknown kind {}", row, kind)))?;
+        let amount: i64 = required(&statement, "amount", &row)?;
+        let stat =
+            match optional::<String>(&statement, "stat")? {
+                Some(s) => Some(Stat::from_name(&s).ok_or_else(|| {
+                    QrpgError::Content(format!("{} has an unknown stat {}", row, s))
+                })?),
+                None if kind == EffectKind::Buff => {
+                    return Err(QrpgError::Content(format!(
+                        "{} is a buff, but doesn't say which stat it raises",
+                        row
+                    )))
+                }
+                None => None,
+            };
+        let status = match optional::<String>(&statement, "status")? {
+            Some(s) => Some(StatusKind::from_name(&s).ok_or_else(|| {
+                QrpgError::Content(format!("{} has an unknown status {}", row, s))
+            })?),
+            None if kind == EffectKind::Status => {
+                return Err(QrpgError::Content(format!(
+                    "{} is a status effect, but doesn't say which status",
+                    row
+                )))
+            }
+            None => None,
+        };
+        v.push(Effect {
+            name,
+            kind,
+            amount: amount as i32,
+            stat,
+            status,
+            turns: optional::<i64>(&statement, "turns")?.unwrap_or(0) as i32,
+        });
+    }
+    Ok(v)
+}
+
+/// Every item that isn't a weapon, with its effect filled in.
+pub fn items_from_db(db: &Connection) -> Result<Vec<Item>> {
+    let effects = effects_from_db(db)?;
+    let mut v = Vec::new();
+    let mut statement = db.prepare("SELECT * FROM items")?;
+    while let State::Row = statement.next()? {
+        let name: String = required(&statement, "name", "an item")?;
+        let row = format!("item {}", name);
+        let category: String = required(&statement, "category", &row)?;
+        let category = match ItemCategory::from_name(&category) {
+            Some(ItemCategory::Weapon) | None => {
+                return Err(QrpgError::Content(format!(
+                    "{} has an unknown category {}",
+                    row, category
+                )))
+            }
+            Some(c) => c,
+        };
+        let weight: f64 = required(&statement, "weight", &row)?;
+        let value: i64 = required(&statement, "value", &row)?;
+        let max_stack: i64 = required(&statement, "max_stack", &row)?;
+        let effect = match optional::<String>(&statement, "effect")? {
+            Some(e) => match effects.iter().find(|effect| effect.name == e) {
+                Some(effect) => Some(effect.clone()),
+                None => {
+                    return Err(QrpgError::Content(format!(
+                        "{} has effect {}, which isn't in the effects table",
+                        row, e
+                    )))
+                }
+            },
+            None => None,
+        };
+        let slot = match optional::<String>(&statement, "slot")? {
+            Some(s) => match EquipSlot::from_name(&s) {
+                Some(slot) => Some(slot),
+                None => {
+                    return Err(QrpgError::Content(format!(
+                        "{} has an unknown slot {}",
+                        row, s
+                    )))
+                }
+            },
+            None => None,
+        };
+        if category == ItemCategory::Armor && slot.is_none() {
+            return Err(QrpgError::Content(format!(
+                "{} is armor without a slot",
+                row
+            )));
+        }
+        v.push(Item {
+            category,
+            max_stack: max_stack.max(1) as u32,
+            effect,
+            description: optional(&statement, "description")?.unwrap_or_default(),
+            slot,
+            defense: optional::<i64>(&statement, "defense")?.unwrap_or(0) as i32,
+            requires: requirements(&statement)?,
+            ..Item::new(&name, weight as f32, value as i32)
+        });
+    }
+    Ok(v)
+}
+
+/// Every skill, with its effect filled in.
+pub fn skills_from_db(db: &Connection) -> Result<Vec<Skill>> {
+    let effects = effects_from_db(db)?;
+    let mut v = Vec::new();
+    let mut statement = db.prepare("SELECT * FROM skills")?;
+    while let State::Row = statement.next()? {
+        let name: String = required(&statement, "name", "a skill")?;
+        let row = format!("skill {}", name);
+        let resource: String = required(&statement, "resource", &row)?;
+        let amount = required::<i64>(&statement, "cost", &row)? as i32;
+        let cost = Cost::from_name(&resource, amount).ok_or_else(|| {
+            QrpgError::Content(format!("{} costs an unknown resource {}", row, resource))
+        })?;
+        let target: String = required(&statement, "target", &row)?;
+        let target = SkillTarget::from_name(&target).ok_or_else(|| {
+            QrpgError::Content(format!("{} has an unknown target {}", row, target))
+        })?;
+        let effect = match optional::<String>(&statement, "effect")? {
+            Some(e) => match effects.iter().find(|effect| effect.name == e) {
+                Some(effect) => Some(effect.clone()),
+                None => {
+                    return Err(QrpgError::Content(format!(
+                        "{} has effect {}, which isn't in the effects table",
+                        row, e
+                    )))
+                }
+            },
+            None => None,
+        };
+        let p: f64 = required(&statement, "physique", &row)?;
+        let t: f64 = required(&statement, "technique", &row)?;
+        let m: f64 = required(&statement, "mystique", &row)?;
+        v.push(Skill {
+            cost,
+            physique_scale: p as f32,
+            technique_scale: t as f32,
+            mystique_scale: m as f32,
+            target,
+            effect,
+            level: optional::<i64>(&statement, "level")?.map(|l| l as i32),
+            price: optional::<i64>(&statement, "price")?.map(|p| p as i32),
+            description: optional(&statement, "description")?.unwrap_or_default(),
+            name,
+        });
+    }
+    Ok(v)
+}
+
+/// The longest character name allowed, in characters.
+pub const MAX_NAME_LEN: usize = 24;
+
+/// Checks that a character name is usable and returns it trimmed.
+///
+/// Any printable name is fine since `slug` makes it safe for the file system,
+/// but it can't be blank, overly long, or contain control characters.
+pub fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(QrpgError::InvalidName("it's empty".into()));
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(QrpgError::InvalidName(format!(
+            "it's longer than {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(QrpgError::InvalidName(
+            "it contains control characters".into(),
+        ));
+    }
+    Ok(name.into())
+}
+
+/// `validate_name` for a new character, who also can't differ only in case
+/// from one of the `taken` names, since `slug` would give them the same
+/// folder on a case-insensitive file system.
+pub fn validate_new_name(name: &str, taken: &[String]) -> Result<String> {
+    let name = validate_name(name)?;
+    match taken
+        .iter()
+        .find(|t| **t != name && t.eq_ignore_ascii_case(&name))
+    {
+        Some(t) => Err(QrpgError::InvalidName(format!(
+            "it only differs from {} in case",
+            t
+        ))),
+        None => Ok(name),
+    }
+}
+
+/// Turns a display name into a directory name that can't escape the save folder.
+///
+/// Letters, digits, spaces, `-` and `_` are kept, so existing saves like
+/// `Quincy` keep their folder. Every other byte is written as `%XX`, which
+/// covers `/`, `\`, `.` and `%` itself, so names never collide on a
+/// case-sensitive file system. Elsewhere `Bob` and `bob` would share a folder,
+/// which `validate_new_name` stops new characters from doing.
+/// Names that Windows reserves for devices get a leading `%`-escape too.
+pub fn slug(name: &str) -> String {
+    let mut s = String::new();
+    for b in name.bytes() {
+        if b.is_ascii_alphanumeric() || b == b' ' || b == b'-' || b == b'_' {
+            s.push(b as char);
+        } else {
+            s.push_str(&format!("%{:02X}", b));
+        }
+    }
+    let upper = s.to_ascii_uppercase();
+    let reserved = ["CON", "PRN", "AUX", "NUL"].contains(&upper.as_str())
+        || ((upper.starts_with("COM") || upper.starts_with("LPT"))
+            && upper.len() == 4
+            && upper.as_bytes()[3].is_ascii_digit());
+    if reserved {
+        s = format!("%{:02X}{}", s.as_bytes()[0], &s[1..]);
+    }
+    s
+}
+
+mod test {
+    #[test]
+    fn test1() {
+        use crate::store::{test_dir, JsonFileStore, SaveStore};
+        let store = JsonFileStore::new(test_dir("test1"));
+        let p = crate::Player::create_random(&mut crate::GameRng::default());
+        store.save(&p, crate::Slot::Numbered(1)).expect("OH NO");
+        let p = store
+            .load("Default", crate::Slot::Numbered(1))
+            .expect("OH NO 2");
+        println!("{:?}", p);
+    }
+
+    #[test]
+    fn test4() {
+        let db = crate::persistence::test_db();
+        let weps = crate::weapons_from_db(&db).unwrap();
+        println!("{:?}", weps);
+    }
+
+    #[test]
+    fn null_weight_is_a_content_error() {
+        use crate::QrpgError;
+        let db = sqlite::open(":memory:").unwrap();
+        db.execute(
+            "CREATE TABLE weapons (name TEXT, weight NUMERIC, value INTEGER, physique NUMERIC, technique NUMERIC, mystique NUMERIC);
+             INSERT INTO weapons VALUES ('Broken', NULL, 1, 1, 1, 1);",
+        )
+        .unwrap();
+        match crate::weapons_from_db(&db) {
+            Err(QrpgError::Content(msg)) => {
+                assert!(msg.contains("Broken") && msg.contains("weight"))
+            }
+            other => panic!("expected a content error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn weapon_columns_are_read_by_name() {
+        let db = sqlite::open(":memory:").unwrap();
+        db.execute(
+            "CREATE TABLE weapons (mystique NUMERIC, name TEXT, technique NUMERIC, value INTEGER, weight NUMERIC, physique NUMERIC);
+             INSERT INTO weapons VALUES (3, 'Odd', 2, 10, 5, 1);",
+        )
+        .unwrap();
+        let w = &crate::weapons_from_db(&db).unwrap()[0];
+        assert_eq!(w.name(), "Odd");
+        assert_eq!(w.physique_scale, 1.0);
+        assert_eq!(w.mystique_scale, 3.0);
+        assert_eq!(w.item.value, 10);
+    }
+
+    #[test]
+    fn enemies_come_with_stats_and_weapons() {
+        let enemies = crate::enemies_from_db(&crate::persistence::test_db()).unwrap();
+        let knight = enemies.iter().find(|e| e.name == "Knight").unwrap();
+        assert_eq!(knight.stats, crate::Stats::new(6, 6, 2));
+        assert_eq!(knight.health, knight.stats.max_health());
+        assert_eq!(knight.weapon.as_ref().unwrap().name(), "Sword");
+        assert!(knight.min_level <= knight.max_level);
+        assert_eq!(knight.loot.as_deref(), Some("knight"));
+
+        let slime = enemies.iter().find(|e| e.name == "Slime").unwrap();
+        assert!(slime.immune.contains(&crate::StatusKind::Poison));
+        assert_eq!(slime.inflicts.as_ref().unwrap().name, "Venom");
+    }
+
+    #[test]
+    fn items_come_with_their_effects() {
+        use crate::items::{EffectKind, ItemCategory};
+        let items = crate::persistence::items_from_db(&crate::persistence::test_db()).unwrap();
+        let potion = items.iter().find(|i| i.name == "Health Potion").unwrap();
+        assert_eq!(potion.category, ItemCategory::Consumable);
+        assert_eq!(potion.effect.as_ref().unwrap().kind, EffectKind::Heal);
+        assert!(potion.usable());
+        let key = items.iter().find(|i| i.name == "Rusty Key").unwrap();
+        assert_eq!(key.max_stack, 1);
+        assert!(!key.usable());
+    }
+
+    #[test]
+    fn unknown_enemy_weapon_is_a_content_error() {
+        use crate::QrpgError;
+        let db = sqlite::open(":memory:").unwrap();
+        db.execute(
+            "CREATE TABLE weapons (name TEXT, weight NUMERIC, value INTEGER, physique NUMERIC, technique NUMERIC, mystique NUMERIC);
+             CREATE TABLE enemies (name TEXT, physique INTEGER, technique INTEGER, mystique INTEGER, weapon TEXT,
+                 min_level INTEGER, max_level INTEGER, xp INTEGER, gold INTEGER, loot TEXT);
+             INSERT INTO enemies VALUES ('Goblin', 1, 1, 1, 'Spork', 1, 2, 3, 4, NULL);",
+        )
+        .unwrap();
+        match crate::enemies_from_db(&db) {
+            Err(QrpgError::Content(msg)) => {
+                assert!(msg.contains("Goblin") && msg.contains("Spork"))
+            }
+            other => panic!("expected a content error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_save_is_an_error() {
+        use crate::store::{test_dir, JsonFileStore, SaveStore};
+        let store = JsonFileStore::new(test_dir("missing"));
+        assert!(store.load("Nobody Here", crate::Slot::Auto).is_err());
+    }
+
+    #[test]
+    fn names_cannot_escape_the_players_dir() {
+        use crate::persistence::{slug, validate_name};
+        assert_eq!(slug("Quincy"), "Quincy");
+        assert_eq!(slug("Sir Bob-the_2nd"), "Sir Bob-the_2nd");
+        assert_eq!(slug("../../etc/x"), "%2E%2E%2F%2E%2E%2Fetc%2Fx");
+        assert_eq!(slug("a\\b"), "a%5Cb");
+        assert_eq!(slug("50%"), "50%25");
+        assert_eq!(slug("con"), "%63on");
+        assert_eq!(slug("Zoë"), "Zo%C3%AB");
+
+        assert_eq!(validate_name("  Quincy ").unwrap(), "Quincy");
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name("bad\nname").is_err());
+        assert!(validate_name(&"x".repeat(25)).is_err());
+    }
+}
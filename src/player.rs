@@ -0,0 +1,718 @@
+use crate::combat::{Attacker, Combatant, Defender};
+use crate::equipment::{Equipment, SLOTS};
+use crate::inventory::{Inventory, Stack};
+use crate::items::{Contents, Effect, EffectKind, Item, ItemCategory, Weapon};
+use crate::persistence::validate_new_name;
+use crate::registry::ContentRegistry;
+use crate::rng::GameRng;
+use crate::stats::{Stat, Stats};
+use crate::status::{Status, Statuses};
+use crate::ui::{choice, Io};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Experience needed for each level, so level 2 comes at 100 xp, level 3 at
+/// 200 and so on.
+pub const XP_PER_LEVEL: i32 = 100;
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub name: String,
+    pub location: String,
+    pub quest: String,
+    pub stats: Stats,
+    pub health: i32,
+    pub stamina: i32,
+    pub mana: i32,
+    pub money: i32,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+    pub triggers: HashMap<String, bool>,
+    /// Seed of the run that created this character, so it can be replayed.
+    #[serde(default)]
+    pub seed: u64,
+    pub level: i32,
+    /// Experience from battles won.
+    #[serde(default)]
+    pub xp: i32,
+    /// The names of the skills and spells the player has learned, looked up
+    /// in content when they're used.
+    #[serde(default)]
+    pub skills: Vec<String>,
+    /// Seconds played before this session, see `total_playtime`.
+    pub playtime: u64,
+    #[serde(skip)]
+    pub session_start: Option<std::time::Instant>,
+    /// Stat bonuses from buffs, which only last until the end of a battle.
+    #[serde(skip)]
+    pub boost: Stats,
+    /// Statuses from the current battle.
+    #[serde(skip)]
+    pub statuses: Statuses,
+}
+
+impl Player {
+    pub fn new(name: &str, stats: Stats) -> Self {
+        Self {
+            name: name.into(),
+            stats,
+            health: stats.max_health(),
+            stamina: stats.max_stamina(),
+            mana: stats.max_mana(),
+            inventory: Inventory::new(),
+            equipment: Equipment::new(),
+            location: "None".into(),
+            quest: "None".into(),
+            money: 100,
+            triggers: HashMap::new(),
+            seed: 0,
+            level: 1,
+            xp: 0,
+            skills: Vec::new(),
+            playtime: 0,
+            session_start: Some(std::time::Instant::now()),
+            boost: Stats::default(),
+            statuses: Statuses::new(),
+        }
+    }
+
+    /// Seconds played including the current session.
+    pub fn total_playtime(&self) -> u64 {
+        let session = self.session_start.map(|s| s.elapsed().as_secs());
+        self.playtime + session.unwrap_or(0)
+    }
+
+    pub fn create_random(rng: &mut GameRng) -> Self {
+        let name = "Default";
+        let stats = Stats::new(
+            rng.gen_range(1, 6),
+            rng.gen_range(1, 6),
+            rng.gen_range(1, 6),
+        );
+        let mut player = Player::new(name, stats);
+        player.seed = rng.seed();
+        player
+    }
+
+    pub fn get_items(&self) -> String {
+        self.inventory.to_string()
+    }
+
+    /// The weight of everything the player has, including what they're wearing.
+    pub fn load(&self) -> f32 {
+        self.inventory.weight() + self.equipment.weight()
+    }
+
+    /// How much the player can carry before they're encumbered.
+    pub fn capacity(&self) -> f32 {
+        self.get_stats().carry_capacity() as f32
+    }
+
+    /// Carrying more than they can manage costs stamina in battle and makes
+    /// the player act last. Buying and picking things up won't do this, but a
+    /// buff wearing off can.
+    pub fn is_encumbered(&self) -> bool {
+        self.load() > self.capacity()
+    }
+
+    /// Whether `quantity` more of something would still be under the capacity.
+    pub fn can_carry(&self, contents: &Contents, quantity: u32) -> bool {
+        self.load() + contents.item().weight * quantity as f32 <= self.capacity()
+    }
+
+    /// Puts something in the inventory if the player can carry it, returning
+    /// whether they could.
+    pub fn pick_up(&mut self, contents: Contents, quantity: u32) -> bool {
+        if !self.can_carry(&contents, quantity) {
+            return false;
+        }
+        self.inventory.add(contents, quantity);
+        true
+    }
+
+    /// The current load for menus, like `Load: 12.5/25`.
+    pub fn load_text(&self) -> String {
+        let mut text = format!("Load: {:.1}/{}", self.load(), self.capacity());
+        if self.is_encumbered() {
+            text.push_str(" (encumbered)");
+        }
+        text
+    }
+
+    /// Applies an effect to the player, never going over their maximums,
+    /// and says what it did. Damage goes to the first of `targets`, and a
+    /// blast hits all of them.
+    pub fn apply(&mut self, effect: &Effect, targets: Vec<&mut dyn Defender>) -> String {
+        let (current, max, what) = match effect.kind {
+            EffectKind::Heal => (&mut self.health, self.stats.max_health(), "health"),
+            EffectKind::Stamina => (&mut self.stamina, self.stats.max_stamina(), "stamina"),
+            EffectKind::Mana => (&mut self.mana, self.stats.max_mana(), "mana"),
+            EffectKind::Buff => {
+                let stat = effect.stat.unwrap_or(Stat::Physique);
+                *self.boost.get_mut(stat) += effect.amount;
+                return format!(
+                    "Your {} rises by {} for the rest of the battle.",
+                    stat, effect.amount
+                );
+            }
+            EffectKind::Damage | EffectKind::Blast => {
+                let hit = if effect.kind == EffectKind::Blast {
+                    targets.len()
+                } else {
+                    1
+                };
+                let hits: Vec<String> = targets
+                    .into_iter()
+                    .take(hit)
+                    .map(|target| {
+                        target.take_damage(effect.amount);
+                        format!("It hits {} for {} damage!", target.name(), effect.amount)
+                    })
+                    .collect();
+                if hits.is_empty() {
+                    return "Nothing happens.".into();
+                }
+                return hits.join(" ");
+            }
+            EffectKind::Status => {
+                let kind = match effect.status {
+                    Some(kind) => kind,
+                    None => return "Nothing happens.".into(),
+                };
+                let status = Status::new(kind, effect.amount, effect.turns);
+                if !kind.harmful() {
+                    self.statuses.add(status);
+                    return format!("You gain {} for {} turns.", kind, effect.turns);
+                }
+                let hits: Vec<String> = targets
+                    .into_iter()
+                    .map(|target| {
+                        if target.afflict(status) {
+                            format!("{} suffers {}!", target.name(), kind)
+                        } else {
+                            format!("{} is immune to {}.", target.name(), kind)
+                        }
+                    })
+                    .collect();
+                if hits.is_empty() {
+                    return "Nothing happens.".into();
+                }
+                return hits.join(" ");
+            }
+        };
+        let before = *current;
+        *current = (*current + effect.amount).min(max).max(before);
+        format!("You recover {} {}.", *current - before, what)
+    }
+
+    pub fn knows(&self, skill: &str) -> bool {
+        self.skills.iter().any(|s| s == skill)
+    }
+
+    /// Adds a skill to the ones the player knows, returning whether it was new.
+    pub fn learn(&mut self, skill: &str) -> bool {
+        if self.knows(skill) {
+            return false;
+        }
+        self.skills.push(skill.to_string());
+        true
+    }
+
+    /// Adds experience, going up a level for every `XP_PER_LEVEL` and learning
+    /// the skills that come with each new level. Returns what happened, a line
+    /// at a time.
+    pub fn gain_xp(&mut self, xp: i32, content: &ContentRegistry) -> Vec<String> {
+        self.xp += xp;
+        let mut lines = Vec::new();
+        while self.xp >= self.level * XP_PER_LEVEL {
+            self.level += 1;
+            lines.push(format!("You reach level {}!", self.level));
+            for skill in content.skills() {
+                if skill.level.is_some_and(|l| l <= self.level) && self.learn(&skill.name) {
+                    lines.push(format!("You learn {}!", skill.name));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Recovers a round's worth of stamina and mana, up to the maximums. An
+    /// encumbered player can't catch their breath, and gets no stamina back.
+    pub fn regen(&mut self) {
+        if !self.is_encumbered() {
+            self.stamina =
+                (self.stamina + self.stats.stamina_regen()).min(self.stats.max_stamina());
+        }
+        self.mana = (self.mana + self.stats.mana_regen()).min(self.stats.max_mana());
+    }
+
+    /// Restores health, stamina and mana completely.
+    pub fn rest(&mut self) {
+        self.health = self.stats.max_health();
+        self.stamina = self.stats.max_stamina();
+        self.mana = self.stats.max_mana();
+    }
+
+    /// Takes one of the stack at `index` out of the inventory if it can be used now.
+    fn take_usable(&mut self, index: usize, in_battle: bool) -> Option<(Item, Effect)> {
+        let (item, effect) = match self.inventory.get(index).map(|s| &s.contents) {
+            Some(Contents::Item(item)) if item.usable() => (item.clone(), item.effect.clone()?),
+            _ => return None,
+        };
+        if effect.battle_only() && !in_battle {
+            return None;
+        }
+        self.inventory.take_one(index);
+        Some((item, effect))
+    }
+
+    /// Uses up one of the stack at `index` in the inventory, returning what happened,
+    /// or None if it isn't something that can be used outside of a battle.
+    pub fn use_item(&mut self, index: usize) -> Option<String> {
+        let (item, effect) = self.take_usable(index, false)?;
+        Some(format!(
+            "You use the {}. {}",
+            item.name,
+            self.apply(&effect, Vec::new())
+        ))
+    }
+
+    /// Uses up one of the stack at `index` during a battle, aimed at `targets`.
+    pub fn use_item_in_battle(
+        &mut self,
+        index: usize,
+        targets: Vec<&mut dyn Defender>,
+    ) -> Option<String> {
+        let (item, effect) = self.take_usable(index, true)?;
+        Some(format!(
+            "You use the {}. {}",
+            item.name,
+            self.apply(&effect, targets)
+        ))
+    }
+
+    /// Moves one of the stack at `index` into the equipment slot at `slot`,
+    /// putting whatever was there back in the inventory. Says what happened,
+    /// or why it couldn't be done.
+    pub fn equip(&mut self, index: usize, slot: usize) -> Result<String, String> {
+        let contents = match self.inventory.get(index) {
+            Some(stack) => stack.contents.clone(),
+            None => return Err("You don't have that.".into()),
+        };
+        let name = contents.item().name.clone();
+        if !Equipment::fits(slot, &contents) {
+            return Err(format!("The {} doesn't go there.", name));
+        }
+        let unmet = self.stats.unmet(&contents.item().requires);
+        if !unmet.is_empty() {
+            let needs: Vec<String> = unmet.iter().map(|(s, n)| format!("{} {}", s, n)).collect();
+            return Err(format!(
+                "You need {} to use the {}.",
+                needs.join(" and "),
+                name
+            ));
+        }
+        self.inventory.take_one(index);
+        if let Some(old) = self.equipment.equip(slot, contents) {
+            self.inventory.add(old, 1);
+        }
+        Ok(format!("You equip the {}.", name))
+    }
+
+    /// Takes off whatever is in the equipment slot at `slot` and puts it in the inventory.
+    pub fn unequip(&mut self, slot: usize) -> Option<String> {
+        let contents = self.equipment.unequip(slot)?;
+        let msg = format!("You take off the {}.", contents.item().name);
+        self.inventory.add(contents, 1);
+        Some(msg)
+    }
+
+    pub fn create_character(io: &mut dyn Io, rng: &GameRng, taken: &[String]) -> Self {
+        io.clear();
+        let mut name = io.input("What is the name of your character?");
+        let name = loop {
+            match validate_new_name(&name, taken) {
+                Ok(valid) => break valid,
+                Err(e) => {
+                    io.clear();
+                    name = io.input(&format!("{}\nWhat is the name of your character?", e));
+                }
+            }
+        };
+        let mut points = 5;
+        let mut stats = Stats::new(1, 1, 1);
+
+        while points > 0 {
+            io.clear();
+            let mut msg = String::from("Physique: Physical Damage, and major for Combat type people.\nTechnique: Technical Damage, and major for Agile type people.\nMystique: Mystical Damage, and major for Magic type people.\n");
+
+            if points == 5 {
+                msg += "Where do you want your first point to go?\n";
+            } else if points > 1 {
+                msg += "Where do you want the next point to go?\n";
+            } else {
+                msg += "Where do you want the last point to go?\n";
+            }
+
+            msg += &format!(
+                "Max Health: {}\nMax Stamina: {}\nMax Mana: {}\nPoints left: {}\nChoose Stats: ",
+                stats.max_health(),
+                stats.max_stamina(),
+                stats.max_mana(),
+                points
+            );
+
+            let point = choice(io, &msg, &["Physique", "Technique", "Mystique"], false);
+
+            if point == 0 {
+                stats.physique += 1;
+                points -= 1;
+            } else if point == 1 {
+                stats.technique += 1;
+                points -= 1;
+            } else {
+                stats.mystique += 1;
+                points -= 1;
+            }
+        }
+        let mut player = Player::new(&name, stats);
+        player.seed = rng.seed();
+        player
+    }
+}
+
+impl Combatant for Player {
+    fn get_stats(&self) -> Stats {
+        self.stats + self.boost
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// What the player fights with when there's nothing in their main hand.
+fn hands() -> Weapon {
+    Weapon {
+        item: Item::new("Hands", 0.0, 0),
+        physique_scale: 1.0,
+        technique_scale: 1.0,
+        mystique_scale: 1.0,
+    }
+}
+
+impl Attacker for Player {
+    fn damage(&self) -> i32 {
+        let damage = match self.equipment.weapon() {
+            Some(w) => w.damage(self),
+            None => hands().damage(self),
+        };
+        (damage - self.statuses.attack_penalty()).max(0)
+    }
+}
+
+impl Defender for Player {
+    fn defense(&self) -> i32 {
+        self.get_stats().physique + self.equipment.defense() + self.statuses.defense_bonus()
+    }
+
+    fn take_damage(&mut self, damage: i32) {
+        self.health -= damage
+    }
+
+    fn afflict(&mut self, status: Status) -> bool {
+        self.statuses.add(status);
+        true
+    }
+}
+
+impl std::fmt::Display for Player {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "(name: {}, level: {}, stats: {}, health: {}, stamina: {}, mana: {}, money: {}, quest: {}, location: {}, triggered: {:?})",
+            self.name, self.level, self.stats, self.health, self.stamina, self.mana, self.money, self.quest, self.location, self.triggers,
+        ))
+    }
+}
+
+/// The next category to show in the inventory, going round to showing everything.
+fn next_filter(filter: Option<ItemCategory>) -> Option<ItemCategory> {
+    use ItemCategory::*;
+    match filter {
+        None => Some(Weapon),
+        Some(Weapon) => Some(Armor),
+        Some(Armor) => Some(Consumable),
+        Some(Consumable) => Some(Material),
+        Some(Material) => Some(Quest),
+        Some(Quest) => Some(Key),
+        Some(Key) => None,
+    }
+}
+
+/// Asks how many to split off a stack of `quantity`, or None if that's not a usable number.
+fn ask_split(io: &mut dyn Io, quantity: u32) -> Option<u32> {
+    let n = io.input(&format!("Split off how many? (1 to {})", quantity - 1));
+    n.trim().parse().ok().filter(|n| *n > 0 && *n < quantity)
+}
+
+/// Lets the player look through their inventory, use what they can, and
+/// sort, filter, split and merge their stacks.
+pub fn inventory(mut player: Player, io: &mut dyn Io) -> Player {
+    let mut filter = None;
+    loop {
+        if player.inventory.is_empty() {
+            io.any_key("Your inventory is empty.");
+            break;
+        }
+        let f = format!(
+            "Your inventory. What do you want to use?\nHP: {}/{}\nSP: {}/{}\nMP: {}/{}\n{}",
+            player.health,
+            player.stats.max_health(),
+            player.stamina,
+            player.stats.max_stamina(),
+            player.mana,
+            player.stats.max_mana(),
+            player.load_text()
+        );
+        let shown = player.inventory.filter(filter);
+        let mut options: Vec<String> = shown
+            .iter()
+            .map(|&n| player.inventory.stacks()[n].to_string())
+            .collect();
+        let showing = match filter {
+            Some(c) => c.to_string(),
+            None => "Everything".into(),
+        };
+        options.push("[Sort by category]".into());
+        options.push(format!("[Showing: {}]", showing));
+        options.push("[Split a stack]".into());
+        options.push("[Merge stacks]".into());
+        let c = choice(io, &f, &options, true);
+        if c < 0 {
+            break;
+        }
+        let c = c as usize;
+        if c >= shown.len() {
+            match c - shown.len() {
+                0 => player.inventory.sort_by_category(),
+                1 => filter = next_filter(filter),
+                2 => {
+                    let stacks: Vec<&Stack> = shown
+                        .iter()
+                        .map(|&n| &player.inventory.stacks()[n])
+                        .collect();
+                    let s = choice(io, "Split which stack?", &stacks, true);
+                    if s < 0 {
+                        continue;
+                    }
+                    let index = shown[s as usize];
+                    let quantity = player.inventory.stacks()[index].quantity;
+                    match ask_split(io, quantity) {
+                        Some(n) => {
+                            player.inventory.split(index, n);
+                        }
+                        None => io.any_key("That stack can't be split like that."),
+                    }
+                }
+                _ => player.inventory.merge(),
+            }
+            continue;
+        }
+        let index = shown[c];
+        match player.use_item(index) {
+            Some(msg) => io.any_key(&msg),
+            None => {
+                let item = player.inventory.stacks()[index].contents.item();
+                let about = if item.description.is_empty() {
+                    item.name.clone()
+                } else {
+                    item.description.clone()
+                };
+                io.any_key(&format!("{}\nYou can't use that here.", about));
+            }
+        }
+    }
+    player
+}
+
+/// Shows what the player has equipped and lets them change it, slot by slot.
+pub fn equipment(mut player: Player, io: &mut dyn Io) -> Player {
+    loop {
+        let f = format!(
+            "Your equipment.\nDamage: {}\nDefense: {}\n{}",
+            player.damage(),
+            player.defense(),
+            player.load_text()
+        );
+        let slots: Vec<String> = SLOTS
+            .iter()
+            .enumerate()
+            .map(|(n, slot)| {
+                let worn = match player.equipment.get(n) {
+                    Some(c) => c.item().name.clone(),
+                    None if n == 0 => hands().item.name,
+                    None => "Nothing".into(),
+                };
+                format!("{}: {}", slot, worn)
+            })
+            .collect();
+        let c = choice(io, &f, &slots, true);
+        if c < 0 {
+            break;
+        }
+        let slot = c as usize;
+        let fits: Vec<usize> = (0..player.inventory.len())
+            .filter(|&n| Equipment::fits(slot, &player.inventory.stacks()[n].contents))
+            .collect();
+        let mut options: Vec<String> = fits
+            .iter()
+            .map(|&n| player.inventory.stacks()[n].contents.to_string())
+            .collect();
+        if player.equipment.get(slot).is_some() {
+            options.push("[Take it off]".into());
+        }
+        if options.is_empty() {
+            io.any_key("You don't have anything that goes there.");
+            continue;
+        }
+        let c = choice(
+            io,
+            &format!("{}: what do you want to equip?", SLOTS[slot]),
+            &options,
+            true,
+        );
+        if c < 0 {
+            continue;
+        }
+        let msg = match fits.get(c as usize) {
+            Some(&index) => match player.equip(index, slot) {
+                Ok(msg) | Err(msg) => msg,
+            },
+            None => player.unequip(slot).unwrap_or_default(),
+        };
+        io.any_key(&msg);
+    }
+    player
+}
+
+mod test {
+    #[test]
+    fn leveling_up_teaches_skills() {
+        use crate::{Player, Stats};
+        let content = crate::registry::test_content();
+        let mut p = Player::new("Novice", Stats::new(5, 5, 5));
+        assert!(p.gain_xp(99, &content).is_empty());
+        let lines = p.gain_xp(150, &content);
+        assert_eq!(p.level, 3);
+        assert_eq!(lines[0], "You reach level 2!");
+        assert!(lines.contains(&"You learn Fireball!".to_string()));
+        assert!(p.knows("Power Strike") && p.knows("Battle Cry"));
+        assert!(!p.knows("Mend"));
+        // Nothing is learned twice.
+        let known = p.skills.len();
+        assert!(!p.learn("Fireball"));
+        assert_eq!(p.skills.len(), known);
+    }
+
+    #[test]
+    fn resting_and_regenerating() {
+        use crate::{Player, Stats};
+        let mut p = Player::new("Tired", Stats::new(10, 10, 10));
+        p.health = 1;
+        p.stamina = 0;
+        p.mana = p.stats.max_mana() - 1;
+        p.regen();
+        assert_eq!(p.stamina, p.stats.stamina_regen());
+        assert_eq!(p.mana, p.stats.max_mana());
+        assert_eq!(p.health, 1);
+        p.rest();
+        assert_eq!(p.health, p.stats.max_health());
+        assert_eq!(p.stamina, p.stats.max_stamina());
+    }
+
+    #[test]
+    fn create_character_asks_again_for_a_bad_name() {
+        use crate::ui::ScriptedIo;
+        use crate::{GameRng, Player};
+        let mut io = ScriptedIo::new(&[0, 0, 1, 2, 2]).with_inputs(&["   ", "bob", " Quincy "]);
+        let p = Player::create_character(&mut io, &GameRng::from_seed(1), &["Bob".into()]);
+        assert_eq!(p.name, "Quincy");
+        assert!(io.transcript().contains("it only differs from Bob in case"));
+        assert_eq!(
+            (p.stats.physique, p.stats.technique, p.stats.mystique),
+            (3, 2, 3)
+        );
+        assert!(io.transcript().contains("That name can't be used"));
+    }
+
+    #[test]
+    fn using_a_potion_heals_up_to_max_health() {
+        use crate::items::Contents;
+        use crate::{Player, Stats};
+        let content = crate::registry::test_content();
+        let mut p = Player::new("Sip", Stats::new(1, 1, 1));
+        p.inventory
+            .add(Contents::Item(content.item("Iron Ore").unwrap().clone()), 1);
+        p.inventory.add(
+            Contents::Item(content.item("Health Potion").unwrap().clone()),
+            2,
+        );
+        p.health = 10;
+        assert!(p.use_item(0).is_none());
+        let msg = p.use_item(1).unwrap();
+        assert_eq!(p.health, p.stats.max_health());
+        assert!(msg.contains(&format!("recover {} health", p.stats.max_health() - 10)));
+        assert_eq!(p.inventory.count("Health Potion"), 1);
+    }
+
+    #[test]
+    fn carrying_too_much() {
+        use crate::items::Contents;
+        use crate::{Player, Stats};
+        let content = crate::registry::test_content();
+        let ore = Contents::Item(content.item("Iron Ore").unwrap().clone());
+        let weight = ore.item().weight;
+        let mut p = Player::new("Mule", Stats::new(1, 1, 1));
+        let fits = (p.capacity() / weight) as u32;
+        assert!(p.pick_up(ore.clone(), fits));
+        assert!(!p.pick_up(ore.clone(), 1));
+        assert!(!p.is_encumbered());
+
+        // A buff lets them carry more, until it wears off.
+        p.boost.physique = 2;
+        assert!(p.pick_up(ore, 1));
+        p.boost = Stats::default();
+        assert!(p.is_encumbered());
+        assert!(p.load_text().ends_with("(encumbered)"));
+    }
+
+    #[test]
+    fn armor_needs_the_stats_and_adds_defense() {
+        use crate::items::Contents;
+        use crate::ui::ScriptedIo;
+        use crate::{Defender, Player, Stats};
+        let content = crate::registry::test_content();
+        let mut p = Player::new("Knight", Stats::new(2, 1, 1));
+        let defense = p.defense();
+        p.inventory.add(
+            Contents::Item(content.item("Chainmail").unwrap().clone()),
+            1,
+        );
+        p.inventory.add(
+            Contents::Item(content.item("Leather Jerkin").unwrap().clone()),
+            1,
+        );
+        assert!(p.equip(0, 3).unwrap_err().contains("You need Physique"));
+        // Body -> the jerkin.
+        let mut io = ScriptedIo::new(&[3, 1]);
+        let mut p = crate::player::equipment(p, &mut io);
+        assert_eq!(p.defense(), defense + 2);
+        assert!(io.transcript().contains("You equip the Leather Jerkin."));
+
+        // Taking it off puts it back in the inventory.
+        assert!(p.unequip(3).is_some());
+        assert_eq!(p.defense(), defense);
+        assert_eq!(p.inventory.count("Leather Jerkin"), 1);
+    }
+}
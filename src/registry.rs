@@ -0,0 +1,227 @@
+use crate::content::fingerprint;
+use crate::enemy::{Enemy, LootDrop};
+use crate::error::Result;
+use crate::items::{Contents, Item, Weapon};
+use crate::persistence::{
+    enemies_from_db, get_db, items_from_db, loot_tables_from_db, skills_from_db, weapons_from_db,
+};
+use crate::rng::GameRng;
+use crate::skills::Skill;
+use rand::Rng;
+use sqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// All of the game's content, loaded from the database once at startup.
+///
+/// Gameplay code looks things up here instead of querying the database, so
+/// opening the shop or starting a fight doesn't reload whole tables.
+#[derive(Debug, Default)]
+pub struct ContentRegistry {
+    weapons: Vec<Weapon>,
+    enemies: Vec<Enemy>,
+    items: Vec<Item>,
+    loot: HashMap<String, Vec<LootDrop>>,
+    skills: Vec<Skill>,
+    weapon_ids: HashMap<String, usize>,
+    enemy_ids: HashMap<String, usize>,
+    item_ids: HashMap<String, usize>,
+    skill_ids: HashMap<String, usize>,
+    /// Where the content was loaded from, for `reload_if_changed`.
+    source: Option<Source>,
+    /// Whether `reload_if_changed` does anything.
+    watching: bool,
+}
+
+/// The database file content was loaded from, when the file was last changed,
+/// and the `fingerprint` of the content in it.
+#[derive(Debug, Clone)]
+struct Source {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    fingerprint: u64,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn index<T>(v: &[T], name: impl Fn(&T) -> &str) -> HashMap<String, usize> {
+    v.iter()
+        .enumerate()
+        .map(|(i, t)| (name(t).to_string(), i))
+        .collect()
+}
+
+impl ContentRegistry {
+    pub fn from_db(db: &Connection) -> Result<Self> {
+        let weapons = weapons_from_db(db)?;
+        let enemies = enemies_from_db(db)?;
+        let items = items_from_db(db)?;
+        let loot = loot_tables_from_db(db)?;
+        let skills = skills_from_db(db)?;
+        Ok(Self {
+            weapon_ids: index(&weapons, |w| w.name()),
+            enemy_ids: index(&enemies, |e| e.name.as_str()),
+            item_ids: index(&items, |i| i.name.as_str()),
+            skill_ids: index(&skills, |s| s.name.as_str()),
+            weapons,
+            enemies,
+            items,
+            loot,
+            skills,
+            source: None,
+            watching: false,
+        })
+    }
+
+    /// Loads the content database at `path`, remembering it for `reload_if_changed`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let db = get_db(path)?;
+        Self::load(path, &db, fingerprint(&db)?)
+    }
+
+    fn load(path: &Path, db: &Connection, fingerprint: u64) -> Result<Self> {
+        let mut content = Self::from_db(db)?;
+        content.source = Some(Source {
+            path: path.into(),
+            modified: modified(path),
+            fingerprint,
+        });
+        Ok(content)
+    }
+
+    /// Turns on `reload_if_changed`, for `--hot-reload`.
+    pub fn watch(&mut self) {
+        self.watching = true;
+    }
+
+    /// Loads the content again if it has changed since it was last loaded,
+    /// returning whether it did. Meant for editing content while the game runs,
+    /// and does nothing unless `watch` was called.
+    ///
+    /// The file is only read when it has been written to, and saves kept in
+    /// the same file don't count as a change. If the new content can't be
+    /// loaded the old content is kept.
+    pub fn reload_if_changed(&mut self) -> Result<bool> {
+        let source = match &mut self.source {
+            Some(source) if self.watching => source,
+            _ => return Ok(false),
+        };
+        let now = modified(&source.path);
+        if now == source.modified {
+            return Ok(false);
+        }
+        let db = get_db(&source.path)?;
+        let now_fingerprint = fingerprint(&db)?;
+        if now_fingerprint == source.fingerprint {
+            source.modified = now;
+            return Ok(false);
+        }
+        let path = source.path.clone();
+        *self = Self::load(&path, &db, now_fingerprint)?;
+        self.watching = true;
+        Ok(true)
+    }
+
+    pub fn weapons(&self) -> &[Weapon] {
+        &self.weapons
+    }
+
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn skills(&self) -> &[Skill] {
+        &self.skills
+    }
+
+    pub fn weapon(&self, name: &str) -> Option<&Weapon> {
+        self.weapon_ids.get(name).map(|&i| &self.weapons[i])
+    }
+
+    pub fn enemy(&self, name: &str) -> Option<&Enemy> {
+        self.enemy_ids.get(name).map(|&i| &self.enemies[i])
+    }
+
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.item_ids.get(name).map(|&i| &self.items[i])
+    }
+
+    pub fn skill(&self, name: &str) -> Option<&Skill> {
+        self.skill_ids.get(name).map(|&i| &self.skills[i])
+    }
+
+    /// The weapon or item called `name`, ready to go in an inventory.
+    pub fn contents(&self, name: &str) -> Option<Contents> {
+        match self.weapon(name) {
+            Some(w) => Some(Contents::Weapon(w.clone())),
+            None => self.item(name).cloned().map(Contents::Item),
+        }
+    }
+
+    pub fn loot_table(&self, name: &str) -> &[LootDrop] {
+        self.loot.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Rolls each drop in the loot table `name`, returning what dropped.
+    pub fn roll_loot(&self, name: &str, rng: &mut GameRng) -> Vec<Contents> {
+        self.loot_table(name)
+            .iter()
+            .filter(|d| rng.gen_bool(d.chance.clamp(0.0, 1.0)))
+            .filter_map(|d| self.contents(&d.item))
+            .collect()
+    }
+}
+
+/// The content that ships with the repo, for tests.
+#[cfg(test)]
+pub(crate) fn test_content() -> ContentRegistry {
+    ContentRegistry::from_db(&crate::persistence::test_db()).unwrap()
+}
+
+mod test {
+    #[test]
+    fn looks_up_by_name() {
+        let content = crate::registry::test_content();
+        assert_eq!(content.weapon("Katana").unwrap().item.value, 20);
+        assert_eq!(content.enemy("Orc").unwrap().stats.physique, 6);
+        assert!(content.enemy("Nobody").is_none());
+        assert_eq!(content.item("Bread").unwrap().max_stack, 20);
+        assert_eq!(content.enemies().len(), 27);
+        assert_eq!(content.loot_table("brute").len(), 3);
+        assert!(content.loot_table("nothing").is_empty());
+    }
+
+    #[test]
+    fn reloads_when_the_database_changes() {
+        use crate::registry::ContentRegistry;
+        use crate::store::test_dir;
+        let path = test_dir("registry").join("qrpg.db");
+        std::fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/qrpg.db"), &path).unwrap();
+        let mut content = ContentRegistry::open(&path).unwrap();
+        content.watch();
+        assert!(!content.reload_if_changed().unwrap());
+
+        // Saves kept in the same file aren't content.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let store = crate::SqliteStore::open(&path).unwrap();
+        let player = crate::Player::new("Saver", crate::Stats::new(1, 1, 1));
+        crate::SaveStore::save(&store, &player, crate::Slot::Auto).unwrap();
+        assert!(!content.reload_if_changed().unwrap());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        sqlite::open(&path)
+            .unwrap()
+            .execute("UPDATE weapons SET value = 99 WHERE name = 'Katana'")
+            .unwrap();
+        assert!(content.reload_if_changed().unwrap());
+        assert_eq!(content.weapon("Katana").unwrap().item.value, 99);
+    }
+}
@@ -0,0 +1,390 @@
+use crate::error::{QrpgError, Result};
+use crate::player::Player;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// The save format written by this version of the game.
+///
+/// Bump this whenever `Player` changes in a way old saves can't be read as-is,
+/// and add a step to `MIGRATIONS` that upgrades the previous version.
+pub const FORMAT_VERSION: u32 = 6;
+
+/// A summary of a save, shown in the load menu without having to look at the player.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    /// Seconds since the unix epoch when the save was written.
+    pub timestamp: u64,
+    pub location: String,
+    pub quest: String,
+    /// Total seconds played.
+    pub playtime: u64,
+    pub level: i32,
+}
+
+impl SaveMetadata {
+    pub fn of(player: &Player) -> Self {
+        Self {
+            timestamp: now(),
+            location: player.location.clone(),
+            quest: player.quest.clone(),
+            playtime: player.total_playtime(),
+            level: player.level,
+        }
+    }
+}
+
+impl std::fmt::Display for SaveMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "Lv {}, {}, quest: {}, played {}h {:02}m, saved {}",
+            self.level,
+            self.location,
+            self.quest,
+            self.playtime / 3600,
+            self.playtime / 60 % 60,
+            format_timestamp(self.timestamp),
+        ))
+    }
+}
+
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats unix seconds as `YYYY-MM-DD HH:MM UTC`.
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let (hour, minute) = (secs / 3600 % 24, secs / 60 % 60);
+    // Howard Hinnant's civil_from_days.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        year, month, day, hour, minute
+    )
+}
+
+/// What actually goes on disk: the player plus the format it was written in.
+///
+/// From version 3 on, `checksum` covers everything else in the file so a save
+/// that was cut short or edited by hand is caught before it's loaded.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub format_version: u32,
+    #[serde(default)]
+    pub checksum: String,
+    pub metadata: SaveMetadata,
+    pub player: Player,
+}
+
+/// 64 bit FNV-1a, as hex. Not cryptographic, it only has to catch accidents.
+pub fn checksum(s: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in s.bytes() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Checks the checksum of a save that should have one, then strips it.
+fn verify(mut value: Value) -> Result<Value> {
+    if version_of(&value)? < 3 {
+        return Ok(value);
+    }
+    let stored = match value.as_object_mut().and_then(|o| o.remove("checksum")) {
+        Some(Value::String(s)) => s,
+        _ => return Err(QrpgError::CorruptSave("missing checksum".into())),
+    };
+    let actual = checksum(&value.to_string());
+    if stored != actual {
+        return Err(QrpgError::CorruptSave(format!(
+            "checksum {} doesn't match contents ({})",
+            stored, actual
+        )));
+    }
+    Ok(value)
+}
+
+impl SaveFile {
+    pub fn new(player: Player) -> Self {
+        let metadata = SaveMetadata::of(&player);
+        let mut player = player;
+        player.playtime = metadata.playtime;
+        Self {
+            format_version: FORMAT_VERSION,
+            checksum: String::new(),
+            metadata,
+            player,
+        }
+    }
+
+    /// The save as JSON, with its checksum filled in.
+    pub fn to_value(&self) -> Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(o) = value.as_object_mut() {
+            o.remove("checksum");
+        }
+        let sum = checksum(&value.to_string());
+        value["checksum"] = json!(sum);
+        Ok(value)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(self.to_value()?.to_string())
+    }
+
+    /// Reads a save of any known version, upgrading it to the current format.
+    pub fn from_value(value: Value) -> Result<Self> {
+        let value = migrate(verify(value)?)?;
+        let mut save: SaveFile =
+            serde_json::from_value(value).map_err(|e| QrpgError::CorruptSave(e.to_string()))?;
+        save.player.session_start = Some(std::time::Instant::now());
+        Ok(save)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self> {
+        Self::from_value(
+            serde_json::from_str(s).map_err(|e| QrpgError::CorruptSave(e.to_string()))?,
+        )
+    }
+}
+
+/// `MIGRATIONS[n]` turns a version `n` save into a version `n + 1` save.
+const MIGRATIONS: &[fn(Value) -> Result<Value>] =
+    &[v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6];
+
+/// Version 0 is the bare `Player` JSON written before saves had an envelope.
+fn v0_to_v1(player: Value) -> Result<Value> {
+    Ok(json!({ "format_version": 1, "player": player }))
+}
+
+/// Version 2 added save metadata, and a level and playtime to the player.
+fn v1_to_v2(mut save: Value) -> Result<Value> {
+    let player = save
+        .get_mut("player")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| QrpgError::Content("save has no player".into()))?;
+    player.entry("level").or_insert(json!(1));
+    player.entry("playtime").or_insert(json!(0));
+    let metadata = json!({
+        "timestamp": 0,
+        "location": player.get("location").cloned().unwrap_or(json!("None")),
+        "quest": player.get("quest").cloned().unwrap_or(json!("None")),
+        "playtime": 0,
+        "level": 1,
+    });
+    save["metadata"] = metadata;
+    save["format_version"] = json!(2);
+    Ok(save)
+}
+
+/// Version 3 added the checksum, which is only checked on read, so there's nothing to convert.
+fn v2_to_v3(mut save: Value) -> Result<Value> {
+    save["format_version"] = json!(3);
+    Ok(save)
+}
+
+/// Version 4 keeps the inventory in stacks, so each old item becomes a stack of one.
+fn v3_to_v4(mut save: Value) -> Result<Value> {
+    let inventory = save
+        .get_mut("player")
+        .and_then(|p| p.get_mut("inventory"))
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| QrpgError::Content("save has no inventory".into()))?;
+    for item in inventory.iter_mut() {
+        *item = json!({ "contents": item.take(), "quantity": 1 });
+    }
+    save["format_version"] = json!(4);
+    Ok(save)
+}
+
+/// Version 5 replaces the one equipped weapon with equipment slots. The weapon
+/// goes in the main hand, unless it was the stand-in for bare hands.
+fn v4_to_v5(mut save: Value) -> Result<Value> {
+    let player = save
+        .get_mut("player")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| QrpgError::Content("save has no player".into()))?;
+    let mut equipment = vec![Value::Null; crate::equipment::SLOTS.len()];
+    match player.remove("equipped") {
+        Some(w) if !w.is_null() && w["item"]["name"] != "Hands" => {
+            equipment[0] = json!({ "Weapon": w })
+        }
+        _ => {}
+    }
+    player.insert("equipment".into(), Value::Array(equipment));
+    save["format_version"] = json!(5);
+    Ok(save)
+}
+
+/// Version 6 saves learned skills by name, so they follow content changes.
+fn v5_to_v6(mut save: Value) -> Result<Value> {
+    let player = save
+        .get_mut("player")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| QrpgError::Content("save has no player".into()))?;
+    if let Some(Value::Array(skills)) = player.get_mut("skills") {
+        for skill in skills.iter_mut() {
+            if let Some(name) = skill.get("name").cloned() {
+                *skill = name;
+            }
+        }
+    }
+    save["format_version"] = json!(6);
+    Ok(save)
+}
+
+fn version_of(value: &Value) -> Result<u32> {
+    match value.get("format_version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| QrpgError::Content(format!("bad save format_version {}", v))),
+    }
+}
+
+pub fn migrate(mut value: Value) -> Result<Value> {
+    let mut version = version_of(&value)?;
+    if version > FORMAT_VERSION {
+        return Err(QrpgError::Content(format!(
+            "save format {} is newer than this game supports ({})",
+            version, FORMAT_VERSION
+        )));
+    }
+    while version < FORMAT_VERSION {
+        value = MIGRATIONS[version as usize](value)?;
+        version = version_of(&value)?;
+    }
+    Ok(value)
+}
+
+mod test {
+    #[test]
+    fn loads_every_historical_version() {
+        use crate::save::SaveFile;
+        let fixtures = [
+            include_str!("../tests/fixtures/saves/v0.json"),
+            include_str!("../tests/fixtures/saves/v1.json"),
+            include_str!("../tests/fixtures/saves/v2.json"),
+            include_str!("../tests/fixtures/saves/v3.json"),
+            include_str!("../tests/fixtures/saves/v4.json"),
+            include_str!("../tests/fixtures/saves/v5.json"),
+            include_str!("../tests/fixtures/saves/v6.json"),
+        ];
+        for fixture in fixtures.iter() {
+            let save = SaveFile::from_json(fixture).unwrap();
+            assert_eq!(save.format_version, crate::save::FORMAT_VERSION);
+            assert_eq!(save.player.name, "Quincy");
+            assert_eq!(save.player.stats.physique, 6);
+            assert_eq!(save.player.triggers.get("char_intro"), Some(&true));
+            assert_eq!(save.player.level, 1);
+            assert_eq!(save.metadata.location, "None");
+        }
+    }
+
+    #[test]
+    fn old_inventories_become_stacks_of_one() {
+        use crate::save::SaveFile;
+        let v2 = include_str!("../tests/fixtures/saves/v2.json").replace(
+            r#""inventory":[]"#,
+            r#""inventory":[{"Item":{"name":"Rock","weight":1.0,"value":1}}]"#,
+        );
+        let save = SaveFile::from_json(&v2).unwrap();
+        assert_eq!(save.player.inventory.count("Rock"), 1);
+        assert_eq!(save.player.inventory.len(), 1);
+
+        let v4 = SaveFile::from_json(include_str!("../tests/fixtures/saves/v4.json")).unwrap();
+        assert_eq!(v4.player.inventory.count("Wolf Pelt"), 3);
+    }
+
+    #[test]
+    fn the_equipped_weapon_moves_to_the_main_hand() {
+        use crate::save::SaveFile;
+        let v4 = SaveFile::from_json(include_str!("../tests/fixtures/saves/v4.json")).unwrap();
+        assert!(v4.player.equipment.weapon().is_none());
+
+        let v2 = include_str!("../tests/fixtures/saves/v2.json").replace(
+            r#""name":"Hands","weight":0.0,"value":0"#,
+            r#""name":"Axe","weight":5.0,"value":10"#,
+        );
+        let save = SaveFile::from_json(&v2).unwrap();
+        assert_eq!(save.player.equipment.weapon().unwrap().name(), "Axe");
+
+        let v5 = SaveFile::from_json(include_str!("../tests/fixtures/saves/v5.json")).unwrap();
+        assert_eq!(v5.player.equipment.weapon().unwrap().name(), "Sword");
+        assert_eq!(v5.player.equipment.defense(), 1);
+    }
+
+    #[test]
+    fn learned_skills_are_kept_by_name() {
+        use crate::save::{checksum, SaveFile};
+        use serde_json::{json, Value};
+        let mut v5: Value =
+            serde_json::from_str(include_str!("../tests/fixtures/saves/v5.json")).unwrap();
+        v5.as_object_mut().unwrap().remove("checksum");
+        v5["player"]["skills"] = json!([{ "name": "Fireball", "cost": { "Mana": 18 } }]);
+        let sum = checksum(&v5.to_string());
+        v5["checksum"] = json!(sum);
+        let save = SaveFile::from_value(v5).unwrap();
+        assert_eq!(save.player.skills, vec!["Fireball".to_string()]);
+
+        let v6 = SaveFile::from_json(include_str!("../tests/fixtures/saves/v6.json")).unwrap();
+        assert!(v6.player.knows("Fireball"));
+    }
+
+    #[test]
+    fn rejects_saves_from_the_future() {
+        use crate::save::SaveFile;
+        assert!(SaveFile::from_json(r#"{"format_version": 999, "player": {}}"#).is_err());
+    }
+
+    #[test]
+    fn round_trips_current_version() {
+        use crate::save::SaveFile;
+        use crate::{Player, Stats};
+        let json = SaveFile::new(Player::new("Round", Stats::new(2, 3, 4)))
+            .to_json()
+            .unwrap();
+        let save = SaveFile::from_json(&json).unwrap();
+        assert_eq!(save.player.stats, Stats::new(2, 3, 4));
+        assert_eq!(save.metadata.level, 1);
+        assert!(save.metadata.timestamp > 0);
+    }
+
+    #[test]
+    fn detects_tampering() {
+        use crate::save::SaveFile;
+        use crate::{Player, QrpgError, Stats};
+        let json = SaveFile::new(Player::new("Rich", Stats::new(1, 1, 1)))
+            .to_json()
+            .unwrap();
+        let json = json.replace("\"money\":100", "\"money\":999999");
+        match SaveFile::from_json(&json) {
+            Err(QrpgError::CorruptSave(_)) => {}
+            other => panic!("expected a corrupt save, got {:?}", other),
+        }
+        match SaveFile::from_json(&json[..json.len() / 2]) {
+            Err(QrpgError::CorruptSave(_)) => {}
+            other => panic!("expected a corrupt save, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        use crate::save::format_timestamp;
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_timestamp(951_825_600), "2000-02-29 12:00 UTC");
+    }
+}
@@ -0,0 +1,179 @@
+use crate::error::{QrpgError, Result};
+use sqlite::{Connection, State};
+
+/// The content database's migrations, oldest first. A migration's version is
+/// its place in this list, counting from 1, so new ones only ever go on the end.
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "weapons and enemies",
+        include_str!("../migrations/0001_weapons_and_enemies.sql"),
+    ),
+    (
+        "enemy stats and loot",
+        include_str!("../migrations/0002_enemy_stats_and_loot.sql"),
+    ),
+    (
+        "items and effects",
+        include_str!("../migrations/0003_items_and_effects.sql"),
+    ),
+    (
+        "effect stats",
+        include_str!("../migrations/0004_effect_stats.sql"),
+    ),
+    (
+        "equipment",
+        include_str!("../migrations/0005_equipment.sql"),
+    ),
+    (
+        "enemy portraits",
+        include_str!("../migrations/0006_enemy_portraits.sql"),
+    ),
+    ("skills", include_str!("../migrations/0007_skills.sql")),
+    (
+        "status effects",
+        include_str!("../migrations/0008_status_effects.sql"),
+    ),
+    (
+        "item loot",
+        include_str!("../migrations/0009_item_loot.sql"),
+    ),
+];
+
+/// The tables that hold game content, as opposed to saves or bookkeeping.
+/// Parents come before the tables that reference them.
+pub const CONTENT_TABLES: &[&str] = &[
+    "weapons",
+    "loot_tables",
+    "effects",
+    "enemies",
+    "items",
+    "skills",
+];
+
+/// The schema version this build of the game expects.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+fn table_exists(db: &Connection, table: &str) -> Result<bool> {
+    let mut statement =
+        db.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")?;
+    statement.bind(1, table)?;
+    Ok(statement.next()? == State::Row)
+}
+
+fn column_exists(db: &Connection, table: &str, column: &str) -> Result<bool> {
+    let statement = db.prepare(format!("SELECT * FROM \"{}\" LIMIT 0", table))?;
+    Ok(statement.names().contains(&column))
+}
+
+/// Works out how far along a database from before `schema_version` existed is,
+/// so the migrations it already has by hand aren't run again.
+fn legacy_version(db: &Connection) -> Result<u32> {
+    if !table_exists(db, "enemies")? || !table_exists(db, "weapons")? {
+        return Ok(0);
+    }
+    if column_exists(db, "enemies", "physique")? {
+        return Ok(2);
+    }
+    Ok(1)
+}
+
+/// The version the database is at, 0 for an empty one.
+pub fn current_version(db: &Connection) -> Result<u32> {
+    if !table_exists(db, "schema_version")? {
+        return legacy_version(db);
+    }
+    let mut statement = db.prepare("SELECT MAX(version) FROM schema_version")?;
+    statement.next()?;
+    Ok(statement.read::<Option<i64>>(0)?.unwrap_or(0) as u32)
+}
+
+fn record(db: &Connection, version: u32) -> Result<()> {
+    let mut statement = db.prepare("INSERT INTO schema_version (version, name) VALUES (?, ?)")?;
+    statement.bind(1, i64::from(version))?;
+    statement.bind(2, MIGRATIONS[version as usize - 1].0)?;
+    statement.next()?;
+    Ok(())
+}
+
+/// Brings the database up to `latest_version`, each migration in its own
+/// transaction, and returns the version it started at.
+///
+/// An up to date database is only read, so this is cheap to call on every open.
+pub fn migrate(db: &Connection) -> Result<u32> {
+    let start = current_version(db)?;
+    let latest = latest_version();
+    if start > latest {
+        return Err(QrpgError::Content(format!(
+            "the database is at schema version {}, but this game only knows up to {}",
+            start, latest
+        )));
+    }
+    if !table_exists(db, "schema_version")? {
+        db.execute(
+            "BEGIN;
+             CREATE TABLE schema_version (version INTEGER PRIMARY KEY, name TEXT NOT NULL);",
+        )?;
+        if let Err(e) = (1..=start).try_for_each(|v| record(db, v)) {
+            db.execute("ROLLBACK")?;
+            return Err(e);
+        }
+        db.execute("COMMIT")?;
+    }
+    for version in start + 1..=latest {
+        let (name, sql) = MIGRATIONS[version as usize - 1];
+        db.execute("BEGIN")?;
+        let applied = db
+            .execute(sql)
+            .map_err(QrpgError::from)
+            .and_then(|_| record(db, version));
+        if let Err(e) = applied {
+            db.execute("ROLLBACK")?;
+            return Err(QrpgError::Content(format!(
+                "migration {} ({}) failed: {}",
+                version, name, e
+            )));
+        }
+        db.execute("COMMIT")?;
+    }
+    Ok(start)
+}
+
+mod test {
+    #[test]
+    fn migrates_an_empty_database() {
+        use crate::schema::{current_version, latest_version, migrate};
+        let db = sqlite::open(":memory:").unwrap();
+        assert_eq!(migrate(&db).unwrap(), 0);
+        assert_eq!(current_version(&db).unwrap(), latest_version());
+        // Running again has nothing left to do.
+        assert_eq!(migrate(&db).unwrap(), latest_version());
+        assert!(crate::enemies_from_db(&db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn upgrades_a_hand_made_database() {
+        use crate::schema::{current_version, latest_version, migrate};
+        let db = sqlite::open(":memory:").unwrap();
+        db.execute(
+            "CREATE TABLE weapons (name TEXT, weight NUMERIC, value INTEGER, physique NUMERIC, technique NUMERIC, mystique NUMERIC);
+             CREATE TABLE enemies (name TEXT);
+             INSERT INTO enemies VALUES ('Slime');",
+        )
+        .unwrap();
+        assert_eq!(migrate(&db).unwrap(), 1);
+        assert_eq!(current_version(&db).unwrap(), latest_version());
+        let enemies = crate::enemies_from_db(&db).unwrap();
+        assert_eq!(enemies[0].name, "Slime");
+        assert_eq!(enemies[0].stats, crate::Stats::new(1, 1, 1));
+    }
+
+    #[test]
+    fn shipped_database_is_up_to_date() {
+        use crate::schema::{current_version, latest_version};
+        // Read only, and not through `get_db`, which would migrate it first.
+        let db = crate::persistence::test_db();
+        assert_eq!(current_version(&db).unwrap(), latest_version());
+    }
+}
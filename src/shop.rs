@@ -0,0 +1,163 @@
+use crate::items::Contents;
+use crate::player::Player;
+use crate::registry::ContentRegistry;
+use crate::ui::{choice, Io};
+
+/// Everything the shop sells: its weapons, then its other items.
+pub fn wares(content: &ContentRegistry) -> Vec<Contents> {
+    let weapons = content.weapons().iter().cloned().map(Contents::Weapon);
+    let items = content
+        .items()
+        .iter()
+        .filter(|i| i.category.for_sale())
+        .cloned()
+        .map(Contents::Item);
+    weapons.chain(items).collect()
+}
+
+pub fn buy(mut player: Player, io: &mut dyn Io, content: &ContentRegistry) -> Player {
+    let wares = wares(content);
+    if wares.is_empty() {
+        io.any_key("The shoppe be closed today, matey!");
+        return player;
+    }
+    loop {
+        let f = format!("What ye be wantin to buy?\nHere are thee wares I have to offer ye'!\nYe have ${}.\nYour inventory [{}].\n{}", player.money, player.get_items(), player.load_text());
+        let c = choice(io, &f, &wares, true);
+        if c < 0 {
+            break;
+        }
+        let w = &wares[c as usize];
+        let value = w.item().value;
+        let a = choice(
+            io,
+            &format!("Ye want to buy a {}, for ${}?", w, value),
+            &["yes", "no"],
+            false,
+        );
+        if a == 0 {
+            if !player.can_carry(w, 1) {
+                io.any_key("Ye'd never carry that, matey! Lighten yer load first.");
+            } else if player.money >= value {
+                io.print("That shall serve you well!");
+                player.money -= value;
+                player.inventory.add(w.clone(), 1);
+            } else {
+                io.any_key("You idiot! You can't afford that, ye swindler!");
+            }
+        }
+    }
+    player
+}
+
+pub fn sell(mut player: Player, io: &mut dyn Io) -> Player {
+    if player.inventory.is_empty() {
+        io.any_key("Ye can't sell, if ye has no valuables!");
+        return player;
+    }
+    loop {
+        let f = format!(
+            "What're ye sellin'!\nYour money ${}\n{}",
+            player.money,
+            player.load_text()
+        );
+        let c = choice(io, &f, player.inventory.stacks(), true);
+        if c == -1 {
+            break player;
+        }
+        let stack = &player.inventory.stacks()[c as usize];
+        let item = stack.contents.item();
+        if !stack.contents.category().for_sale() {
+            io.any_key(&format!("I've no use for ye {}, matey.", item.name));
+            continue;
+        }
+        let f = format!(
+            "I'll take ye, {} for ${}\nYe be sure, ye want to sell thee?",
+            item.name, item.value
+        );
+        let value = item.value;
+        if choice(io, &f, &["yes", "no"], true) == 0 {
+            player.money += value;
+            player.inventory.take_one(c as usize);
+        }
+    }
+}
+
+pub fn shop(mut player: Player, io: &mut dyn Io, content: &ContentRegistry) -> Player {
+    loop {
+        let c = choice(
+            io,
+            "Welcome to ye ol' shoppe! What must ye be buyin, or sellin?..",
+            &["Buy", "Sell"],
+            true,
+        );
+        if c == 0 {
+            player = buy(player, io, content);
+        } else if c == 1 {
+            player = sell(player, io);
+        } else {
+            io.print("Cya later buddy!");
+            break;
+        }
+    }
+    player
+}
+
+mod test {
+    #[test]
+    fn test3() {
+        use crate::ui::ScriptedIo;
+        let p = crate::Player::create_random(&mut crate::GameRng::default());
+        println!("{:?}", p);
+        // Buy -> first weapon -> yes, then quit out of both menus.
+        let mut io = ScriptedIo::new(&[0, 0, 0]);
+        let p = crate::shop(p, &mut io, &crate::registry::test_content());
+        println!("{:?}", p);
+        assert_eq!(p.inventory.len(), 1);
+        assert!(p.money < 100);
+    }
+
+    #[test]
+    fn sell_returns_money() {
+        use crate::ui::ScriptedIo;
+        let content = crate::registry::test_content();
+        let p = crate::shop(crate::Player::create_random(&mut crate::GameRng::default()), &mut ScriptedIo::new(&[0, 0, 0]), &content);
+        let spent = 100 - p.money;
+        // Sell -> first item -> yes.
+        let p = crate::shop(p, &mut ScriptedIo::new(&[1, 0, 0]), &content);
+        assert!(p.inventory.is_empty());
+        assert_eq!(p.money, 100);
+        assert!(spent > 0);
+    }
+
+    #[test]
+    fn sells_items_but_not_quest_items() {
+        use crate::ui::ScriptedIo;
+        use crate::{Contents, Player, Stats};
+        let content = crate::registry::test_content();
+        let wares = crate::shop::wares(&content);
+        let potion = wares
+            .iter()
+            .position(|w| w.item().name == "Health Potion")
+            .unwrap();
+        assert!(wares.iter().all(|w| w.item().name != "Rusty Key"));
+
+        // Buy -> the potion -> yes.
+        let p = Player::new("Shopper", Stats::new(1, 1, 1));
+        let mut io = ScriptedIo::new(&[0, potion as i32, 0]);
+        let mut p = crate::shop(p, &mut io, &content);
+        assert_eq!(p.inventory.count("Health Potion"), 1);
+        assert_eq!(p.money, 85);
+
+        p.inventory.add(
+            Contents::Item(content.item("Rusty Key").unwrap().clone()),
+            1,
+        );
+        // Sell -> the key, which is refused, then the potion -> yes.
+        let mut io = ScriptedIo::new(&[1, 1, 0, 0]);
+        let p = crate::shop(p, &mut io, &content);
+        assert_eq!(p.inventory.len(), 1);
+        assert_eq!(p.money, 100);
+        assert!(io.transcript().contains("no use for ye Rusty Key"));
+    }
+}
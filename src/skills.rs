@@ -0,0 +1,213 @@
+use crate::items::Effect;
+use crate::player::Player;
+use crate::registry::ContentRegistry;
+use crate::stats::Stats;
+use crate::ui::{choice, Io};
+use serde::{Deserialize, Serialize};
+
+/// What an action takes out of the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cost {
+    Free,
+    Stamina(i32),
+    Mana(i32),
+}
+
+impl Cost {
+    /// `amount` of the resource named as it's written in the skills table.
+    pub fn from_name(resource: &str, amount: i32) -> Option<Self> {
+        Some(match resource {
+            _ if amount == 0 => Cost::Free,
+            "stamina" => Cost::Stamina(amount),
+            "mana" => Cost::Mana(amount),
+            _ => return None,
+        })
+    }
+
+    /// The cost as menus show it, like ` (15 SP)`.
+    pub fn label(&self) -> String {
+        match self {
+            Cost::Free => String::new(),
+            Cost::Stamina(n) => format!(" ({} SP)", n),
+            Cost::Mana(n) => format!(" ({} MP)", n),
+        }
+    }
+
+    pub fn affordable(&self, player: &Player) -> bool {
+        match *self {
+            Cost::Free => true,
+            Cost::Stamina(n) => player.stamina >= n,
+            Cost::Mana(n) => player.mana >= n,
+        }
+    }
+
+    /// Takes the cost from the player, who should be able to afford it. One
+    /// who can't is left with nothing, rather than going below zero.
+    pub fn pay(&self, player: &mut Player) {
+        match *self {
+            Cost::Free => {}
+            Cost::Stamina(n) => player.stamina = (player.stamina - n).max(0),
+            Cost::Mana(n) => player.mana = (player.mana - n).max(0),
+        }
+    }
+
+    /// Why the player can't do something they can't afford.
+    pub fn refusal(&self) -> &'static str {
+        match self {
+            Cost::Mana(_) => "You don't have enough mana.",
+            _ => "You don't have enough stamina.",
+        }
+    }
+}
+
+/// Who a skill is used on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillTarget {
+    /// One enemy, picked by the player.
+    Enemy,
+    /// Every enemy still standing.
+    All,
+    /// The player themselves.
+    #[serde(rename = "self")]
+    Myself,
+}
+
+impl SkillTarget {
+    /// The target as it's written in the skills table.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "enemy" => SkillTarget::Enemy,
+            "all" => SkillTarget::All,
+            "self" => SkillTarget::Myself,
+            _ => return None,
+        })
+    }
+}
+
+/// A row of the skills table: a special attack or spell.
+///
+/// Its damage scales from the user's stats the way a weapon's does, and it
+/// can have an effect too, like a heal or a buff. Players learn skills on
+/// reaching their `level`, or from a trainer for their `price`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Skill {
+    pub name: String,
+    pub cost: Cost,
+    pub physique_scale: f32,
+    pub technique_scale: f32,
+    pub mystique_scale: f32,
+    pub target: SkillTarget,
+    pub effect: Option<Effect>,
+    /// The level it's learned at, if it's learned by leveling.
+    pub level: Option<i32>,
+    /// What a trainer charges for it, if one teaches it.
+    pub price: Option<i32>,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Skill {
+    /// How hard the skill hits for someone with `stats`.
+    pub fn power(&self, stats: &Stats) -> i32 {
+        ((self.physique_scale * stats.physique as f32)
+            + (self.technique_scale * stats.technique as f32)
+            + (self.mystique_scale * stats.mystique as f32)) as i32
+    }
+}
+
+impl std::fmt::Display for Skill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.name, self.cost.label())
+    }
+}
+
+/// The town trainer, who teaches skills for money.
+pub fn trainer(mut player: Player, io: &mut dyn Io, content: &ContentRegistry) -> Player {
+    loop {
+        let lessons: Vec<&Skill> = content
+            .skills()
+            .iter()
+            .filter(|s| s.price.is_some() && !player.knows(&s.name))
+            .collect();
+        if lessons.is_empty() {
+            io.any_key("There's nothing more I can teach you.");
+            break;
+        }
+        let options: Vec<String> = lessons
+            .iter()
+            .map(|s| format!("{} - ${}: {}", s, s.price.unwrap_or(0), s.description))
+            .collect();
+        let f = format!("What would you like to learn?\nYou have ${}.", player.money);
+        let c = choice(io, &f, &options, true);
+        if c < 0 {
+            break;
+        }
+        let skill = lessons[c as usize];
+        let price = skill.price.unwrap_or(0);
+        if player.money < price {
+            io.any_key("Come back when you can pay for the lesson.");
+            continue;
+        }
+        player.money -= price;
+        player.learn(&skill.name);
+        io.any_key(&format!("You learn {}!", skill.name));
+    }
+    player
+}
+
+mod test {
+    #[test]
+    fn skills_scale_with_stats() {
+        use crate::skills::{Cost, SkillTarget};
+        use crate::Stats;
+        let content = crate::registry::test_content();
+        let fireball = content.skill("Fireball").unwrap();
+        assert_eq!(fireball.cost, Cost::Mana(18));
+        assert_eq!(fireball.target, SkillTarget::Enemy);
+        assert_eq!(fireball.to_string(), "Fireball (18 MP)");
+        assert!(fireball.power(&Stats::new(1, 1, 10)) > fireball.power(&Stats::new(10, 10, 1)));
+        assert!(content.skill("Mend").unwrap().effect.is_some());
+    }
+
+    #[test]
+    fn paying_never_goes_below_zero() {
+        use crate::skills::Cost;
+        use crate::{Player, Stats};
+        let mut p = Player::new("Spent", Stats::new(1, 1, 1));
+        p.stamina = 10;
+        p.mana = 5;
+        Cost::Stamina(4).pay(&mut p);
+        assert_eq!(p.stamina, 6);
+        Cost::Stamina(20).pay(&mut p);
+        Cost::Mana(20).pay(&mut p);
+        assert_eq!((p.stamina, p.mana), (0, 0));
+    }
+
+    #[test]
+    fn trainers_teach_for_money() {
+        use crate::skills::trainer;
+        use crate::ui::ScriptedIo;
+        use crate::{Player, Stats};
+        let content = crate::registry::test_content();
+        let lessons: Vec<&str> = content
+            .skills()
+            .iter()
+            .filter(|s| s.price.is_some())
+            .map(|s| s.name.as_str())
+            .collect();
+        let mut p = Player::new("Student", Stats::new(5, 5, 5));
+        p.money = 60;
+        let mend = lessons.iter().position(|&s| s == "Mend").unwrap();
+        let p = trainer(p, &mut ScriptedIo::new(&[mend as i32]), &content);
+        assert!(p.knows("Mend"));
+        assert_eq!(p.money, 10);
+
+        // Too poor for anything else, and Mend isn't offered again.
+        let mut io = ScriptedIo::new(&[0]);
+        let p = trainer(p, &mut io, &content);
+        assert!(io.transcript().contains("Come back when you can pay"));
+        assert!(!io.transcript().contains("Mend (15 MP)"));
+        assert_eq!(p.skills.len(), 1);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::{QrpgError, Result};
+use crate::persistence::{slug, validate_name};
+use crate::save::SaveFile;
+use crate::store::{LoadedSave, SaveEntry, SaveStore, Slot};
+use serde_json::{json, Map, Value};
+use sqlite::{Connection, State};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS players (
+    slug TEXT NOT NULL,
+    slot TEXT NOT NULL,
+    name TEXT NOT NULL,
+    format_version INTEGER NOT NULL,
+    checksum TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    location TEXT NOT NULL,
+    quest TEXT NOT NULL,
+    playtime INTEGER NOT NULL,
+    level INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (slug, slot)
+);
+CREATE TABLE IF NOT EXISTS player_inventory (
+    slug TEXT NOT NULL,
+    slot TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (slug, slot, position)
+);
+CREATE TABLE IF NOT EXISTS player_triggers (
+    slug TEXT NOT NULL,
+    slot TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value INTEGER NOT NULL,
+    PRIMARY KEY (slug, slot, name)
+);
+";
+
+/// Saves as rows in a SQLite database, by default the content database itself.
+///
+/// Each save is a row in `players`, with its inventory and story triggers
+/// split out into `player_inventory` and `player_triggers`. The rest of the
+/// player is kept as JSON in `players.data`, so the save format's migrations
+/// and checksum apply just like they do for the JSON files.
+pub struct SqliteStore {
+    db: Connection,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and the save tables if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = Connection::open(path)?;
+        db.execute(SCHEMA)?;
+        Ok(Self { db })
+    }
+
+    /// Runs `f` in a transaction, rolling back if it fails.
+    fn transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.db.execute("BEGIN")?;
+        match f() {
+            Ok(v) => {
+                self.db.execute("COMMIT")?;
+                Ok(v)
+            }
+            Err(e) => {
+                self.db.execute("ROLLBACK")?;
+                Err(e)
+            }
+        }
+    }
+
+    fn delete(&self, key: &str, slot: &str) -> Result<()> {
+        for table in ["players", "player_inventory", "player_triggers"].iter() {
+            let mut statement = self
+                .db
+                .prepare(format!("DELETE FROM {} WHERE slug = ? AND slot = ?", table))?;
+            statement.bind(1, key)?;
+            statement.bind(2, slot)?;
+            while let State::Row = statement.next()? {}
+        }
+        Ok(())
+    }
+}
+
+fn take(o: &mut Map<String, Value>, key: &str) -> Value {
+    o.remove(key).unwrap_or(Value::Null)
+}
+
+impl SaveStore for SqliteStore {
+    fn write(&self, save: &SaveFile, slot: Slot) -> Result<()> {
+        let name = validate_name(&save.player.name)?;
+        let key = slug(&name);
+        let slot = slot.key();
+        let mut value = save.to_value()?;
+        let o = value.as_object_mut().unwrap();
+        let checksum = take(o, "checksum");
+        let metadata = take(o, "metadata");
+        let mut player = take(o, "player");
+        let player = player.as_object_mut().unwrap();
+        let inventory = take(player, "inventory");
+        let triggers = take(player, "triggers");
+
+        self.transaction(|| {
+            self.delete(&key, &slot)?;
+            let mut statement = self.db.prepare(
+                "INSERT INTO players (slug, slot, name, format_version, checksum, timestamp, location, quest, playtime, level, data)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )?;
+            statement.bind(1, key.as_str())?;
+            statement.bind(2, slot.as_str())?;
+            statement.bind(3, name.as_str())?;
+            statement.bind(4, i64::from(save.format_version))?;
+            statement.bind(5, checksum.as_str().unwrap_or(""))?;
+            statement.bind(6, metadata["timestamp"].as_i64().unwrap_or(0))?;
+            statement.bind(7, metadata["location"].as_str().unwrap_or(""))?;
+            statement.bind(8, metadata["quest"].as_str().unwrap_or(""))?;
+            statement.bind(9, metadata["playtime"].as_i64().unwrap_or(0))?;
+            statement.bind(10, metadata["level"].as_i64().unwrap_or(0))?;
+            statement.bind(11, Value::Object(player.clone()).to_string().as_str())?;
+            statement.next()?;
+
+            let mut statement = self.db.prepare(
+                "INSERT INTO player_inventory (slug, slot, position, data) VALUES (?, ?, ?, ?)",
+            )?;
+            for (n, item) in inventory.as_array().into_iter().flatten().enumerate() {
+                statement.reset()?;
+                statement.bind(1, key.as_str())?;
+                statement.bind(2, slot.as_str())?;
+                statement.bind(3, n as i64)?;
+                statement.bind(4, item.to_string().as_str())?;
+                statement.next()?;
+            }
+
+            let mut statement = self.db.prepare(
+                "INSERT INTO player_triggers (slug, slot, name, value) VALUES (?, ?, ?, ?)",
+            )?;
+            for (trigger, value) in triggers.as_object().into_iter().flatten() {
+                statement.reset()?;
+                statement.bind(1, key.as_str())?;
+                statement.bind(2, slot.as_str())?;
+                statement.bind(3, trigger.as_str())?;
+                statement.bind(4, value.as_bool().unwrap_or(false) as i64)?;
+                statement.next()?;
+            }
+            Ok(())
+        })
+    }
+
+    fn read(&self, name: &str, slot: Slot) -> Result<LoadedSave> {
+        let key = slug(&validate_name(name)?);
+        let slot = slot.key();
+        let mut statement = self.db.prepare(
+            "SELECT format_version, checksum, timestamp, location, quest, playtime, level, data
+             FROM players WHERE slug = ? AND slot = ?",
+        )?;
+        statement.bind(1, key.as_str())?;
+        statement.bind(2, slot.as_str())?;
+        if statement.next()? != State::Row {
+            return Err(QrpgError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no save for {} in {}", name, slot),
+            )));
+        }
+        let mut player: Value = serde_json::from_str(&statement.read::<String>(7)?)?;
+        let mut value = json!({
+            "format_version": statement.read::<i64>(0)?,
+            "checksum": statement.read::<String>(1)?,
+            "metadata": {
+                "timestamp": statement.read::<i64>(2)?,
+                "location": statement.read::<String>(3)?,
+                "quest": statement.read::<String>(4)?,
+                "playtime": statement.read::<i64>(5)?,
+                "level": statement.read::<i64>(6)?,
+            },
+        });
+
+        let mut inventory = Vec::new();
+        let mut statement = self.db.prepare(
+            "SELECT data FROM player_inventory WHERE slug = ? AND slot = ? ORDER BY position",
+        )?;
+        statement.bind(1, key.as_str())?;
+        statement.bind(2, slot.as_str())?;
+        while let State::Row = statement.next()? {
+            inventory.push(serde_json::from_str::<Value>(
+                &statement.read::<String>(0)?,
+            )?);
+        }
+
+        let mut triggers = Map::new();
+        let mut statement = self
+            .db
+            .prepare("SELECT name, value FROM player_triggers WHERE slug = ? AND slot = ?")?;
+        statement.bind(1, key.as_str())?;
+        statement.bind(2, slot.as_str())?;
+        while let State::Row = statement.next()? {
+            triggers.insert(
+                statement.read::<String>(0)?,
+                json!(statement.read::<i64>(1)? != 0),
+            );
+        }
+
+        player["inventory"] = Value::Array(inventory);
+        player["triggers"] = Value::Object(triggers);
+        value["player"] = player;
+        Ok(LoadedSave {
+            save: SaveFile::from_value(value)?,
+            recovered: false,
+        })
+    }
+
+    fn list(&self) -> Result<Vec<SaveEntry>> {
+        let mut v = Vec::new();
+        let mut statement = self.db.prepare(
+            "SELECT name, slot, timestamp, location, quest, playtime, level FROM players",
+        )?;
+        while let State::Row = statement.next()? {
+            let slot = match Slot::from_key(&statement.read::<String>(1)?) {
+                Some(slot) => slot,
+                None => continue,
+            };
+            v.push(SaveEntry {
+                name: statement.read(0)?,
+                slot,
+                metadata: crate::save::SaveMetadata {
+                    timestamp: statement.read::<i64>(2)? as u64,
+                    location: statement.read(3)?,
+                    quest: statement.read(4)?,
+                    playtime: statement.read::<i64>(5)? as u64,
+                    level: statement.read::<i64>(6)? as i32,
+                },
+            });
+        }
+        v.sort_by(|a, b| (&a.name, a.slot).cmp(&(&b.name, b.slot)));
+        Ok(v)
+    }
+}
+
+mod test {
+    #[test]
+    fn round_trips_a_player() {
+        use crate::items::{Contents, Item};
+        use crate::sqlite_store::SqliteStore;
+        use crate::store::{test_dir, SaveStore};
+        use crate::{Player, Slot, Stats};
+        let store = SqliteStore::open(test_dir("sqlite").join("saves.db")).unwrap();
+        let mut p = Player::new("Sql", Stats::new(3, 2, 1));
+        p.inventory
+            .add(Contents::Item(Item::new("Rock", 1.0, 1)), 1);
+        p.inventory
+            .add(Contents::Item(Item::new("Stick", 0.5, 2)), 1);
+        p.triggers.insert("char_intro".into(), true);
+        store.save(&p, Slot::Numbered(1)).unwrap();
+        // Saving again replaces the old rows instead of adding to them.
+        store.save(&p, Slot::Numbered(1)).unwrap();
+
+        let loaded = store.load("Sql", Slot::Numbered(1)).unwrap();
+        assert_eq!(loaded.stats, p.stats);
+        assert_eq!(loaded.inventory, p.inventory);
+        assert_eq!(loaded.triggers, p.triggers);
+        assert_eq!(store.list().unwrap().len(), 1);
+        assert!(store.load("Sql", Slot::Auto).is_err());
+    }
+
+    #[test]
+    fn migrates_between_stores() {
+        use crate::sqlite_store::SqliteStore;
+        use crate::store::{migrate_saves, test_dir, JsonFileStore, SaveStore};
+        use crate::{Player, Slot, Stats};
+        let dir = test_dir("migrate");
+        let json = JsonFileStore::new(dir.join("Players"));
+        json.save(&Player::new("A", Stats::new(1, 1, 1)), Slot::Auto)
+            .unwrap();
+        json.save(&Player::new("B", Stats::new(2, 2, 2)), Slot::Numbered(3))
+            .unwrap();
+        let sql = SqliteStore::open(dir.join("saves.db")).unwrap();
+        assert_eq!(migrate_saves(&json, &sql).unwrap(), 2);
+        let before: Vec<String> = json.list().unwrap().iter().map(|e| e.to_string()).collect();
+        let after: Vec<String> = sql.list().unwrap().iter().map(|e| e.to_string()).collect();
+        assert_eq!(before, after);
+        assert_eq!(
+            sql.load("B", Slot::Numbered(3)).unwrap().stats,
+            Stats::new(2, 2, 2)
+        );
+    }
+}
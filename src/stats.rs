@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, PartialEq, Eq, Ord, PartialOrd, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Stats {
+    pub physique: i32,
+    pub technique: i32,
+    pub mystique: i32,
+}
+
+/// One of the three stats, for content that refers to a stat by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stat {
+    Physique,
+    Technique,
+    Mystique,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 3] = [Stat::Physique, Stat::Technique, Stat::Mystique];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "physique" => Stat::Physique,
+            "technique" => Stat::Technique,
+            "mystique" => Stat::Mystique,
+            _ => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for Stat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stat::Physique => "Physique",
+            Stat::Technique => "Technique",
+            Stat::Mystique => "Mystique",
+        })
+    }
+}
+
+impl std::ops::Add for Stats {
+    type Output = Stats;
+
+    fn add(self, other: Stats) -> Stats {
+        Stats {
+            physique: self.physique + other.physique,
+            technique: self.technique + other.technique,
+            mystique: self.mystique + other.mystique,
+        }
+    }
+}
+
+impl Stats {
+    pub fn get(&self, stat: Stat) -> i32 {
+        match stat {
+            Stat::Physique => self.physique,
+            Stat::Technique => self.technique,
+            Stat::Mystique => self.mystique,
+        }
+    }
+
+    pub fn get_mut(&mut self, stat: Stat) -> &mut i32 {
+        match stat {
+            Stat::Physique => &mut self.physique,
+            Stat::Technique => &mut self.technique,
+            Stat::Mystique => &mut self.mystique,
+        }
+    }
+
+    pub fn new<T: Into<i32>>(physique: T, technique: T, mystique: T) -> Self {
+        let physique = physique.into();
+        let mystique = mystique.into();
+        let technique = technique.into();
+        Self {
+            physique,
+            technique,
+            mystique,
+        }
+    }
+
+    pub fn max_health(&self) -> i32 {
+        (self.physique * 5) + (self.technique * 3) + (self.mystique * 4) + 20
+    }
+
+    pub fn max_stamina(&self) -> i32 {
+        (self.physique * 4) + (self.technique * 5) + (self.mystique * 3) + 20
+    }
+
+    pub fn max_mana(&self) -> i32 {
+        (self.physique * 3) + (self.technique * 4) + (self.mystique * 5) + 20
+    }
+
+    /// Stamina recovered each round of a battle.
+    pub fn stamina_regen(&self) -> i32 {
+        3 + self.technique / 2
+    }
+
+    /// Mana recovered each round of a battle.
+    pub fn mana_regen(&self) -> i32 {
+        3 + self.mystique / 2
+    }
+
+    pub fn carry_capacity(&self) -> i32 {
+        10 + (self.physique * 5)
+    }
+
+    /// The stats in `required` that these fall short of, with how much is needed.
+    pub fn unmet(&self, required: &Stats) -> Vec<(Stat, i32)> {
+        Stat::ALL
+            .iter()
+            .filter(|&&s| self.get(s) < required.get(s))
+            .map(|&s| (s, required.get(s)))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "Physique: {}, Technique: {}, Mystique: {}",
+            self.physique, self.technique, self.mystique
+        ))
+    }
+}
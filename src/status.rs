@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+/// A lasting condition on someone in a battle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusKind {
+    /// Damage every turn. Doses stack.
+    Poison,
+    /// Damage every turn.
+    Bleed,
+    /// Damage every turn.
+    Burn,
+    /// Loses their turns.
+    Stun,
+    /// Heals every turn.
+    Regen,
+    /// Raises defense.
+    DefenseUp,
+    /// Lowers the damage they deal.
+    AttackDown,
+}
+
+impl StatusKind {
+    /// The kind as it's written in content tables.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "poison" => StatusKind::Poison,
+            "bleed" => StatusKind::Bleed,
+            "burn" => StatusKind::Burn,
+            "stun" => StatusKind::Stun,
+            "regen" => StatusKind::Regen,
+            "defense_up" => StatusKind::DefenseUp,
+            "attack_down" => StatusKind::AttackDown,
+            _ => return None,
+        })
+    }
+
+    /// Whether it's something to put on an enemy rather than on yourself.
+    pub fn harmful(&self) -> bool {
+        !matches!(self, StatusKind::Regen | StatusKind::DefenseUp)
+    }
+
+    /// Whether a second dose adds to the first. Anything else keeps the
+    /// stronger of the two, for the longer of the two.
+    pub fn stacks(&self) -> bool {
+        *self == StatusKind::Poison
+    }
+}
+
+impl std::fmt::Display for StatusKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StatusKind::Poison => "Poison",
+            StatusKind::Bleed => "Bleed",
+            StatusKind::Burn => "Burn",
+            StatusKind::Stun => "Stun",
+            StatusKind::Regen => "Regen",
+            StatusKind::DefenseUp => "Defense up",
+            StatusKind::AttackDown => "Attack down",
+        })
+    }
+}
+
+/// A status and how much is left of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    pub kind: StatusKind,
+    /// Damage or healing each turn, or how far it moves attack or defense.
+    pub potency: i32,
+    /// Turns left, counting the one it's ticked at the start of.
+    pub turns: i32,
+}
+
+impl Status {
+    pub fn new(kind: StatusKind, potency: i32, turns: i32) -> Self {
+        Self {
+            kind,
+            potency,
+            turns,
+        }
+    }
+}
+
+/// What a turn's worth of statuses did to someone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tick {
+    pub damage: i32,
+    pub healing: i32,
+    pub stunned: bool,
+}
+
+/// Everything affecting one fighter, at most one of each kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Statuses {
+    active: Vec<Status>,
+}
+
+impl Statuses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn get(&self, kind: StatusKind) -> Option<&Status> {
+        self.active.iter().find(|s| s.kind == kind)
+    }
+
+    /// Adds `status`, combining it with one of the same kind by `stacks`.
+    pub fn add(&mut self, status: Status) {
+        match self.active.iter_mut().find(|s| s.kind == status.kind) {
+            Some(s) if status.kind.stacks() => {
+                s.potency += status.potency;
+                s.turns = s.turns.max(status.turns);
+            }
+            Some(s) => {
+                s.potency = s.potency.max(status.potency);
+                s.turns = s.turns.max(status.turns);
+            }
+            None => self.active.push(status),
+        }
+    }
+
+    /// Applies a turn of every status, at the start of the turn, and counts
+    /// their turns down. Statuses that run out are removed.
+    pub fn tick(&mut self) -> Tick {
+        let mut tick = Tick::default();
+        for s in self.active.iter_mut() {
+            match s.kind {
+                StatusKind::Poison | StatusKind::Bleed | StatusKind::Burn => {
+                    tick.damage += s.potency
+                }
+                StatusKind::Regen => tick.healing += s.potency,
+                StatusKind::Stun => tick.stunned = true,
+                StatusKind::DefenseUp | StatusKind::AttackDown => {}
+            }
+            s.turns -= 1;
+        }
+        self.active.retain(|s| s.turns > 0);
+        tick
+    }
+
+    /// How much less damage attacks do.
+    pub fn attack_penalty(&self) -> i32 {
+        self.get(StatusKind::AttackDown).map_or(0, |s| s.potency)
+    }
+
+    /// How much extra damage is stopped.
+    pub fn defense_bonus(&self) -> i32 {
+        self.get(StatusKind::DefenseUp).map_or(0, |s| s.potency)
+    }
+}
+
+impl std::fmt::Display for Statuses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let all: Vec<String> = self
+            .active
+            .iter()
+            .map(|s| format!("{} {} ({}t)", s.kind, s.potency, s.turns))
+            .collect();
+        f.write_str(&all.join(", "))
+    }
+}
+
+mod test {
+    #[test]
+    fn poison_stacks_and_the_rest_refresh() {
+        use crate::status::{Status, StatusKind, Statuses};
+        let mut statuses = Statuses::new();
+        statuses.add(Status::new(StatusKind::Poison, 3, 2));
+        statuses.add(Status::new(StatusKind::Poison, 2, 3));
+        statuses.add(Status::new(StatusKind::Burn, 5, 1));
+        statuses.add(Status::new(StatusKind::Burn, 4, 2));
+        assert_eq!(
+            statuses.get(StatusKind::Poison),
+            Some(&Status::new(StatusKind::Poison, 5, 3))
+        );
+        assert_eq!(
+            statuses.get(StatusKind::Burn),
+            Some(&Status::new(StatusKind::Burn, 5, 2))
+        );
+        assert_eq!(statuses.to_string(), "Poison 5 (3t), Burn 5 (2t)");
+    }
+
+    #[test]
+    fn statuses_tick_down_and_run_out() {
+        use crate::status::{Status, StatusKind, Statuses, Tick};
+        let mut statuses = Statuses::new();
+        statuses.add(Status::new(StatusKind::Bleed, 4, 2));
+        statuses.add(Status::new(StatusKind::Regen, 3, 2));
+        statuses.add(Status::new(StatusKind::Stun, 0, 1));
+        statuses.add(Status::new(StatusKind::DefenseUp, 6, 1));
+        assert_eq!(statuses.defense_bonus(), 6);
+        let tick = statuses.tick();
+        assert_eq!(
+            tick,
+            Tick {
+                damage: 4,
+                healing: 3,
+                stunned: true
+            }
+        );
+        assert!(statuses.get(StatusKind::Stun).is_none());
+        assert_eq!(statuses.defense_bonus(), 0);
+        assert!(!statuses.tick().stunned);
+        assert!(statuses.is_empty());
+    }
+}
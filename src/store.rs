@@ -0,0 +1,365 @@
+use crate::error::{QrpgError, Result};
+use crate::persistence::{slug, validate_name};
+use crate::player::Player;
+use crate::save::{SaveFile, SaveMetadata};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Where among a character's saves a save lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Slot {
+    Auto,
+    Numbered(u32),
+}
+
+/// How many numbered slots each character gets.
+pub const SLOTS: u32 = 3;
+
+impl Slot {
+    /// Every slot a character can have, autosave first.
+    pub fn all() -> Vec<Slot> {
+        let mut v = vec![Slot::Auto];
+        v.extend((1..=SLOTS).map(Slot::Numbered));
+        v
+    }
+
+    /// A stable name for the slot, used in file names and database rows.
+    pub fn key(&self) -> String {
+        match self {
+            Slot::Auto => "autosave".into(),
+            Slot::Numbered(n) => format!("slot{}", n),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Slot> {
+        if key == "autosave" {
+            return Some(Slot::Auto);
+        }
+        key.strip_prefix("slot")?.parse().ok().map(Slot::Numbered)
+    }
+}
+
+impl std::fmt::Display for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Slot::Auto => f.write_str("Autosave"),
+            Slot::Numbered(n) => write!(f, "Slot {}", n),
+        }
+    }
+}
+
+/// A save found in a store, as listed by the load menu.
+#[derive(Debug, Clone)]
+pub struct SaveEntry {
+    pub name: String,
+    pub slot: Slot,
+    pub metadata: SaveMetadata,
+}
+
+impl std::fmt::Display for SaveEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {} - {}", self.name, self.slot, self.metadata)
+    }
+}
+
+/// A loaded save, and whether it had to come from a backup.
+#[derive(Debug)]
+pub struct LoadedSave {
+    pub save: SaveFile,
+    pub recovered: bool,
+}
+
+/// Somewhere player saves can be kept.
+///
+/// Stores only move whole `SaveFile`s around, so the save format, its
+/// migrations and its checksum work the same whichever store is used.
+pub trait SaveStore {
+    /// Writes a save, replacing whatever was in that slot.
+    fn write(&self, save: &SaveFile, slot: Slot) -> Result<()>;
+    fn read(&self, name: &str, slot: Slot) -> Result<LoadedSave>;
+    /// Every save in the store, sorted by name and slot.
+    fn list(&self) -> Result<Vec<SaveEntry>>;
+
+    fn save(&self, player: &Player, slot: Slot) -> Result<()> {
+        self.write(&SaveFile::new(player.clone()), slot)
+    }
+
+    fn load(&self, name: &str, slot: Slot) -> Result<Player> {
+        Ok(self.read(name, slot)?.save.player)
+    }
+}
+
+/// Copies every save from one store into another, returning how many were copied.
+/// Saves keep their metadata, so they look the same in the load menu afterwards.
+pub fn migrate_saves(from: &dyn SaveStore, to: &dyn SaveStore) -> Result<usize> {
+    let entries = from.list()?;
+    for entry in entries.iter() {
+        let loaded = from.read(&entry.name, entry.slot)?;
+        to.write(&loaded.save, entry.slot)?;
+    }
+    Ok(entries.len())
+}
+
+/// Saves as JSON files, one folder per character: `<dir>/<slug>/<slot>.json`.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    dir: PathBuf,
+}
+
+fn read_save(path: &Path) -> Result<SaveFile> {
+    let mut file = std::fs::File::open(path)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+    SaveFile::from_json(&s)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(suffix);
+    s.into()
+}
+
+/// Writes `contents` so that `path` always holds either the old or the new save.
+///
+/// The new save goes to a temp file that is synced and renamed over `path`.
+/// Before that, the previous save is copied to `<path>.bak` if it's readable,
+/// so a broken save never overwrites a good backup.
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let tmp = with_suffix(path, ".tmp");
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+    }
+    if read_save(path).is_ok() {
+        std::fs::copy(path, with_suffix(path, ".bak"))?;
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reads a save, falling back to its `.bak` if the save itself is unreadable.
+/// A recovered backup is copied back into place and the broken save kept as `.corrupt`.
+fn read_with_backup(path: &Path) -> Result<LoadedSave> {
+    let err = match read_save(path) {
+        Ok(save) => {
+            return Ok(LoadedSave {
+                save,
+                recovered: false,
+            })
+        }
+        Err(e) => e,
+    };
+    let bak = with_suffix(path, ".bak");
+    let save = match read_save(&bak) {
+        Ok(save) => save,
+        Err(_) => return Err(err),
+    };
+    if path.exists() {
+        std::fs::rename(path, with_suffix(path, ".corrupt"))?;
+    }
+    std::fs::copy(&bak, path)?;
+    Ok(LoadedSave {
+        save,
+        recovered: true,
+    })
+}
+
+impl JsonFileStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().into(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn save_path(&self, name: &str, slot: Slot) -> Result<PathBuf> {
+        let name = validate_name(name)?;
+        Ok(self
+            .dir
+            .join(slug(&name))
+            .join(format!("{}.json", slot.key())))
+    }
+
+    /// Moves saves from before slots existed (`<dir>/<name>.txt`) into slot 1.
+    /// The old file is left alone so nothing is lost if this goes wrong.
+    /// Files that can't be read are skipped, and returned with the reason why.
+    pub fn upgrade_legacy_saves(&self) -> Result<Vec<(PathBuf, QrpgError)>> {
+        let mut skipped = Vec::new();
+        if !self.dir.exists() {
+            return Ok(skipped);
+        }
+        for file in std::fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            if path.extension().is_none_or(|e| e != "txt") {
+                continue;
+            }
+            let save = match read_save(&path) {
+                Ok(save) => save,
+                Err(e) => {
+                    skipped.push((path, e));
+                    continue;
+                }
+            };
+            if !self
+                .save_path(&save.player.name, Slot::Numbered(1))?
+                .exists()
+            {
+                self.save(&save.player, Slot::Numbered(1))?;
+            }
+        }
+        Ok(skipped)
+    }
+}
+
+impl SaveStore for JsonFileStore {
+    fn write(&self, save: &SaveFile, slot: Slot) -> Result<()> {
+        let path = self.save_path(&save.player.name, slot)?;
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        write_atomically(&path, &save.to_json()?)
+    }
+
+    /// Recovers from the slot's backup if the save is corrupted.
+    fn read(&self, name: &str, slot: Slot) -> Result<LoadedSave> {
+        read_with_backup(&self.save_path(name, slot)?)
+    }
+
+    /// Saves that can't be read are skipped rather than hiding the rest. A
+    /// damaged save is listed from its backup, but only `read` repairs it.
+    fn list(&self) -> Result<Vec<SaveEntry>> {
+        let mut v = Vec::new();
+        if !self.dir.exists() {
+            return Ok(v);
+        }
+        for dir in std::fs::read_dir(&self.dir)? {
+            let dir = dir?;
+            if !dir.file_type()?.is_dir() {
+                continue;
+            }
+            for file in std::fs::read_dir(dir.path())? {
+                let path = file?.path();
+                if path.extension().is_none_or(|e| e != "json") {
+                    continue;
+                }
+                let slot = match path
+                    .file_stem()
+                    .and_then(|s| Slot::from_key(&s.to_string_lossy()))
+                {
+                    Some(slot) => slot,
+                    None => continue,
+                };
+                let backup = with_suffix(&path, ".bak");
+                if let Ok(save) = read_save(&path).or_else(|_| read_save(&backup)) {
+                    v.push(SaveEntry {
+                        name: save.player.name,
+                        slot,
+                        metadata: save.metadata,
+                    });
+                }
+            }
+        }
+        v.sort_by(|a, b| (&a.name, a.slot).cmp(&(&b.name, b.slot)));
+        Ok(v)
+    }
+}
+
+/// A fresh, empty directory for a test to play in.
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("qrpg-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+mod test {
+    #[test]
+    fn slots_are_listed_with_metadata() {
+        use crate::store::{test_dir, JsonFileStore, SaveStore};
+        use crate::{Player, Slot, Stats};
+        let store = JsonFileStore::new(test_dir("slots"));
+        let mut p = Player::new("Slotted", Stats::new(1, 2, 3));
+        p.location = "Forest".into();
+        store.save(&p, Slot::Auto).unwrap();
+        store.save(&p, Slot::Numbered(2)).unwrap();
+        let saves = store.list().unwrap();
+        assert_eq!(saves.len(), 2);
+        assert_eq!(saves[0].slot, Slot::Auto);
+        assert_eq!(saves[1].slot, Slot::Numbered(2));
+        assert_eq!(saves[1].metadata.location, "Forest");
+    }
+
+    #[test]
+    fn corrupt_save_recovers_from_backup() {
+        use crate::store::{test_dir, JsonFileStore, SaveStore};
+        use crate::{Player, Slot, Stats};
+        let store = JsonFileStore::new(test_dir("backup"));
+        let mut p = Player::new("Backup", Stats::new(1, 1, 1));
+        store.save(&p, Slot::Numbered(1)).unwrap();
+        p.money = 5;
+        store.save(&p, Slot::Numbered(1)).unwrap();
+        // Cut the latest save short, as if the game crashed mid-write.
+        let path = store.save_path("Backup", Slot::Numbered(1)).unwrap();
+        let s = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &s[..s.len() / 2]).unwrap();
+
+        // Listing shows the backup without touching any files.
+        assert_eq!(store.list().unwrap()[0].name, "Backup");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), s[..s.len() / 2]);
+        assert!(!path.with_extension("json.corrupt").exists());
+
+        let loaded = store.read("Backup", Slot::Numbered(1)).unwrap();
+        assert!(loaded.recovered);
+        assert_eq!(loaded.save.player.money, 100);
+        // The backup is back in place, so the next load is clean.
+        assert!(!store.read("Backup", Slot::Numbered(1)).unwrap().recovered);
+    }
+
+    #[test]
+    fn unsafe_names_keep_their_display_name() {
+        use crate::store::{test_dir, JsonFileStore, SaveStore};
+        use crate::{Player, Slot, Stats};
+        let dir = test_dir("sneaky");
+        let store = JsonFileStore::new(&dir);
+        let p = Player::new("../Sneaky", Stats::new(1, 1, 1));
+        store.save(&p, Slot::Auto).unwrap();
+        assert!(dir.join("%2E%2E%2FSneaky/autosave.json").exists());
+        let path = store.save_path("../../etc/x", Slot::Auto).unwrap();
+        assert_eq!(path.parent().unwrap().parent().unwrap(), dir);
+        let loaded = store.load("../Sneaky", Slot::Auto).unwrap();
+        assert_eq!(loaded.name, "../Sneaky");
+        assert!(store.list().unwrap().iter().any(|s| s.name == "../Sneaky"));
+    }
+
+    #[test]
+    fn legacy_saves_move_into_slot_one() {
+        use crate::store::{test_dir, JsonFileStore, SaveStore};
+        use crate::Slot;
+        let dir = test_dir("legacy");
+        std::fs::write(
+            dir.join("Quincy.txt"),
+            include_str!("../tests/fixtures/saves/v0.json"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("Broken.txt"), "{\"name\": ").unwrap();
+        let store = JsonFileStore::new(&dir);
+        let skipped = store.upgrade_legacy_saves().unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, dir.join("Broken.txt"));
+        let saves = store.list().unwrap();
+        assert_eq!(saves.len(), 1);
+        assert_eq!(saves[0].slot, Slot::Numbered(1));
+        assert!(dir.join("Quincy.txt").exists());
+    }
+
+    #[test]
+    fn slot_keys_round_trip() {
+        use crate::Slot;
+        for slot in Slot::all() {
+            assert_eq!(Slot::from_key(&slot.key()), Some(slot));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::battle::{Battle, BattleResult};
+use crate::enemy::Enemy;
+use crate::error::Result;
+use crate::player::{equipment, inventory, Player};
+use crate::registry::ContentRegistry;
+use crate::rng::GameRng;
+use crate::shop::shop;
+use crate::skills::trainer;
+use crate::store::{SaveStore, Slot};
+use crate::ui::{choice, Io};
+use rand::Rng;
+
+pub fn char_intro(
+    mut player: Player,
+    io: &mut dyn Io,
+    store: &dyn SaveStore,
+    content: &ContentRegistry,
+    rng: &mut GameRng,
+) -> Player {
+    io.any_key("Ahoy there, traveler! Would ye be interested in helpn' dis ol' merchant with a task?");
+    io.any_key("The task be simple, ya! You help me travel to the next city over yonder. (Points eastwards)");
+    io.any_key("Then i'll pay yee when we get to the city, ya?");
+    io.any_key("Alright! Sounds great. Let's get going'");
+    io.any_key("Hours later after traveling for the rest of the day. You wake up with masked shadow figures over your tent!");
+    io.any_key("They attack you visciously, knock you out, and take all your belongings.");
+    io.any_key("You feel a massive splash of water as you go in and out of conciousness.");
+    io.any_key("You wake up hours later..With no food and water..");
+    io.any_key("Those bastards took all of your equipment, you need to head to the nearest town to fully recover..");
+    io.any_key("As you fumble around along a dirt path back to any nearby civilization..you hear rustling in the bushes from the forst!");
+    io.any_key("You get ready for anythin!");
+    io.any_key("Out of the bushes come a tiny, but a rabid and agitated animal ready to strike!");
+    io.any_key("You must fight it off or die! Even if you only have half of your strength left..");
+    player.health = player.stats.max_health() / 2;
+    let bunny = match content.enemy("Rabbit") {
+        Some(rabbit) => rabbit.clone(),
+        None => Enemy::new("Rabbit").with_stats(1, 1, 1),
+    };
+    let result = Battle::new(&mut player, bunny)
+        .no_escape()
+        .run(io, content, rng);
+    if result == BattleResult::Defeat {
+        io.any_key("Everything goes dark... but you come to, somehow still breathing.");
+        player.health = 1;
+    }
+    player.triggers.insert("char_intro".into(), true);
+    if let Err(e) = store.save(&player, Slot::Auto) {
+        io.any_key(&format!("Your progress couldn't be saved.\n{}", e));
+    }
+    player
+}
+
+/// Heads out of town and fights whatever turns up for the player's level,
+/// sometimes a pair of them. Losing sends them back to town with 1 health and
+/// half their money.
+pub fn explore(
+    mut player: Player,
+    io: &mut dyn Io,
+    content: &ContentRegistry,
+    rng: &mut GameRng,
+) -> Player {
+    let count = if rng.gen_bool(1.0 / 3.0) { 2 } else { 1 };
+    let enemies: Result<Vec<Enemy>> = (0..count)
+        .map(|_| Enemy::random_for_level(content, player.level, rng))
+        .collect();
+    let enemies = match enemies {
+        Ok(enemies) => enemies,
+        Err(e) => {
+            io.any_key(&format!("There's nothing out there.\n{}", e));
+            return player;
+        }
+    };
+    let met: Vec<String> = enemies.iter().map(|e| format!("a {}", e.name)).collect();
+    io.any_key(&format!(
+        "You wander out of town and run into {}!",
+        met.join(" and ")
+    ));
+    match Battle::against(&mut player, enemies).run(io, content, rng) {
+        BattleResult::Defeat => {
+            player.health = 1;
+            player.money /= 2;
+            io.any_key("You wake up back in town, bruised and with a lighter purse.");
+        }
+        BattleResult::Fled => io.any_key("You hurry back to town."),
+        BattleResult::Victory { .. } => {}
+    }
+    player
+}
+
+/// Picks up changes to the content while the game runs, if `--hot-reload`
+/// is on. Content that fails to load leaves the old content in place.
+pub fn reload_content(io: &mut dyn Io, content: &mut ContentRegistry) {
+    match content.reload_if_changed() {
+        Ok(true) => io.print("Content reloaded."),
+        Ok(false) => {}
+        Err(e) => io.any_key(&format!("Couldn't reload content, keeping the old.\n{}", e)),
+    }
+}
+
+/// The town the player recovers in, where they can shop and sort out their things.
+pub fn town(
+    mut player: Player,
+    io: &mut dyn Io,
+    content: &mut ContentRegistry,
+    rng: &mut GameRng,
+) -> Player {
+    player.location = "Town".into();
+    loop {
+        reload_content(io, content);
+        let c = choice(
+            io,
+            "You're in town. What do you want to do?",
+            &[
+                "Shop",
+                "Inventory",
+                "Equipment",
+                "Explore",
+                "Rest",
+                "Trainer",
+                "Leave",
+            ],
+            true,
+        );
+        match c {
+            0 => player = shop(player, io, content),
+            1 => player = inventory(player, io),
+            2 => player = equipment(player, io),
+            3 => player = explore(player, io, content, rng),
+            4 => {
+                player.rest();
+                io.any_key("You rest at the inn, and wake up feeling fresh.");
+            }
+            5 => player = trainer(player, io, content),
+            _ => break,
+        }
+    }
+    player
+}
+
+pub fn story(
+    player: Player,
+    io: &mut dyn Io,
+    store: &dyn SaveStore,
+    content: &mut ContentRegistry,
+    rng: &mut GameRng,
+) -> Player {
+    let mut player = player;
+    if player.triggers.is_empty() {
+        player = char_intro(player, io, store, content, rng);
+    }
+    town(player, io, content, rng)
+}
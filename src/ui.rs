@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+/// Everything the game flows need from the player's screen and keyboard.
+///
+/// Flows such as the shop or character creation take an `&mut dyn Io` instead
+/// of talking to the terminal, so they can be driven by `ConsoleIo` when
+/// playing and by `ScriptedIo` in tests and bots.
+pub trait Io {
+    fn clear(&mut self);
+    fn print(&mut self, msg: &str);
+    fn any_key(&mut self, msg: &str);
+    fn input(&mut self, msg: &str) -> String;
+    /// Returns the index of the selected option, or -1 if `quit` is allowed and the player quit.
+    fn choice(&mut self, display: &str, options: &[String], quit: bool) -> i32;
+}
+
+/// Convenience wrapper so callers can pass any displayable options.
+pub fn choice<T: std::fmt::Display>(io: &mut dyn Io, display: &str, options: &[T], quit: bool) -> i32 {
+    let options: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+    io.choice(display, &options, quit)
+}
+
+/// Dims a menu option that can't be picked right now. Terminals without
+/// colors just get the text.
+pub fn greyed(text: &str) -> String {
+    console::style(text).dim().to_string()
+}
+
+pub struct ConsoleIo {
+    term: console::Term,
+}
+
+impl Default for ConsoleIo {
+    fn default() -> Self {
+        Self {
+            term: console::Term::stdout(),
+        }
+    }
+}
+
+impl Io for ConsoleIo {
+    fn clear(&mut self) {
+        self.term.clear_screen().unwrap();
+    }
+
+    fn print(&mut self, msg: &str) {
+        println!("{}", msg);
+    }
+
+    fn any_key(&mut self, msg: &str) {
+        println!("{}", msg);
+        self.term.read_key().unwrap();
+    }
+
+    fn input(&mut self, msg: &str) -> String {
+        println!("{}", msg);
+        let mut s = String::new();
+        match std::io::stdin().read_line(&mut s) {
+            Ok(_) => s.trim().into(),
+            Err(_) => s,
+        }
+    }
+
+    fn choice(&mut self, display: &str, options: &[String], quit: bool) -> i32 {
+        let mut selection: i32 = 0;
+        loop {
+            self.clear();
+            println!("{}", display);
+            if quit {
+                println!("Type Q to quit.");
+            }
+            for (n, v) in options.iter().enumerate() {
+                if selection == n as i32 {
+                    println!("{}: {} <-", n + 1, v);
+                } else {
+                    println!("{}: {}", n + 1, v);
+                }
+            }
+            let k = self.term.read_key().expect("Failed to key: in choice()");
+            if k == console::Key::ArrowUp {
+                selection -= 1;
+            }
+            if k == console::Key::ArrowDown {
+                selection += 1;
+            }
+            if quit && k == console::Key::Char('q') {
+                selection = -1;
+                break selection;
+            }
+            for c in "1234567890".chars() {
+                if k == console::Key::Char(c) {
+                    selection = c.to_string().parse::<i32>().unwrap() - 1;
+                }
+            }
+            if k == console::Key::Enter {
+                break selection;
+            }
+            if selection < 0 {
+                selection = 0;
+            }
+            if selection > options.len() as i32 - 1 {
+                selection = options.len() as i32 - 1;
+            }
+        }
+    }
+}
+
+/// An in-memory `Io` that answers from a script and records everything shown.
+///
+/// When the scripted choices run out, menus that can be quit return -1 so
+/// flows unwind on their own; any other menu panics, since the script is wrong.
+#[derive(Default, Debug)]
+pub struct ScriptedIo {
+    pub choices: VecDeque<i32>,
+    pub inputs: VecDeque<String>,
+    pub output: Vec<String>,
+}
+
+impl ScriptedIo {
+    pub fn new(choices: &[i32]) -> Self {
+        Self {
+            choices: choices.iter().copied().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_inputs(mut self, inputs: &[&str]) -> Self {
+        self.inputs = inputs.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Everything printed so far, one entry per message, with the options of
+    /// each menu numbered the way `ConsoleIo` shows them.
+    pub fn transcript(&self) -> String {
+        self.output.join("\n")
+    }
+}
+
+impl Io for ScriptedIo {
+    fn clear(&mut self) {}
+
+    fn print(&mut self, msg: &str) {
+        self.output.push(msg.into());
+    }
+
+    fn any_key(&mut self, msg: &str) {
+        self.output.push(msg.into());
+    }
+
+    fn input(&mut self, msg: &str) -> String {
+        self.output.push(msg.into());
+        self.inputs.pop_front().expect("ScriptedIo ran out of inputs")
+    }
+
+    fn choice(&mut self, display: &str, options: &[String], quit: bool) -> i32 {
+        self.output.push(display.into());
+        for (n, v) in options.iter().enumerate() {
+            self.output.push(format!("{}: {}", n + 1, v));
+        }
+        match self.choices.pop_front() {
+            Some(c) => c,
+            None if quit => -1,
+            None => panic!("ScriptedIo ran out of choices at: {} {:?}", display, options),
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn scripted_choices_then_quit() {
+        use crate::ui::{choice, Io, ScriptedIo};
+        let mut io = ScriptedIo::new(&[1]).with_inputs(&["Quincy"]);
+        assert_eq!(choice(&mut io, "Pick", &["a", "b"], true), 1);
+        assert_eq!(choice(&mut io, "Pick", &["a", "b"], true), -1);
+        assert_eq!(io.input("Name?"), "Quincy");
+        assert!(io.transcript().contains("Name?"));
+    }
+}
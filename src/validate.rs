@@ -0,0 +1,583 @@
+use crate::equipment::EquipSlot;
+use crate::error::Result;
+use crate::items::{EffectKind, ItemCategory};
+use crate::schema::{current_version, latest_version};
+use crate::skills::{Cost, SkillTarget};
+use crate::stats::Stat;
+use crate::status::StatusKind;
+use sqlite::{Connection, State, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Something wrong with the content, and where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub table: String,
+    /// The row's `rowid`, or None for problems with the table itself.
+    pub row: Option<i64>,
+    /// The row's name, if it has a usable one.
+    pub name: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.row, &self.name) {
+            (Some(row), Some(name)) => write!(f, "{} row {} ({}): ", self.table, row, name)?,
+            (Some(row), None) => write!(f, "{} row {}: ", self.table, row)?,
+            (None, _) => write!(f, "{}: ", self.table)?,
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// What a column has to hold.
+enum Check {
+    /// The row's name: text that no other row in the table has.
+    Name,
+    /// Text that can't be blank, but that other rows can share.
+    Label,
+    Text,
+    /// A word the game knows, going by the `from_name` of what it names.
+    Known(fn(&str) -> bool),
+    /// A number in `min..=max`, and a whole one if `integer`.
+    Number {
+        min: f64,
+        max: f64,
+        integer: bool,
+    },
+    /// A value of `column` in some row of one of `tables`.
+    Reference {
+        tables: &'static [&'static str],
+        column: &'static str,
+    },
+}
+
+struct Column {
+    name: &'static str,
+    required: bool,
+    check: Check,
+}
+
+struct Table {
+    name: &'static str,
+    columns: &'static [Column],
+}
+
+const fn number(name: &'static str, min: f64, max: f64) -> Column {
+    Column {
+        name,
+        required: true,
+        check: Check::Number {
+            min,
+            max,
+            integer: false,
+        },
+    }
+}
+
+const fn integer(name: &'static str, min: f64, max: f64) -> Column {
+    Column {
+        name,
+        required: true,
+        check: Check::Number {
+            min,
+            max,
+            integer: true,
+        },
+    }
+}
+
+const fn name() -> Column {
+    Column {
+        name: "name",
+        required: true,
+        check: Check::Name,
+    }
+}
+
+const fn known(name: &'static str, required: bool, known: fn(&str) -> bool) -> Column {
+    Column {
+        name,
+        required,
+        check: Check::Known(known),
+    }
+}
+
+const fn reference(
+    name: &'static str,
+    required: bool,
+    tables: &'static [&'static str],
+    column: &'static str,
+) -> Column {
+    Column {
+        name,
+        required,
+        check: Check::Reference { tables, column },
+    }
+}
+
+/// The rules for every content table.
+const TABLES: &[Table] = &[
+    Table {
+        name: "weapons",
+        columns: &[
+            name(),
+            number("weight", 0.0, 1000.0),
+            integer("value", 0.0, 100_000.0),
+            number("physique", 0.0, 5.0),
+            number("technique", 0.0, 5.0),
+            number("mystique", 0.0, 5.0),
+            integer("min_physique", 0.0, 99.0),
+            integer("min_technique", 0.0, 99.0),
+            integer("min_mystique", 0.0, 99.0),
+        ],
+    },
+    Table {
+        name: "enemies",
+        columns: &[
+            name(),
+            integer("physique", 0.0, 99.0),
+            integer("technique", 0.0, 99.0),
+            integer("mystique", 0.0, 99.0),
+            reference("weapon", false, &["weapons"], "name"),
+            integer("min_level", 1.0, 100.0),
+            integer("max_level", 1.0, 100.0),
+            integer("xp", 0.0, 100_000.0),
+            integer("gold", 0.0, 100_000.0),
+            reference("loot", false, &["loot_tables"], "name"),
+            Column {
+                name: "portrait",
+                required: false,
+                check: Check::Text,
+            },
+            Column {
+                name: "intro",
+                required: false,
+                check: Check::Text,
+            },
+            Column {
+                name: "immunities",
+                required: false,
+                check: Check::Text,
+            },
+            reference("inflicts", false, &["effects"], "name"),
+        ],
+    },
+    Table {
+        name: "loot_tables",
+        columns: &[
+            Column {
+                name: "name",
+                required: true,
+                check: Check::Label,
+            },
+            reference("item", true, &["weapons", "items"], "name"),
+            number("chance", 0.0, 1.0),
+        ],
+    },
+    Table {
+        name: "effects",
+        columns: &[
+            name(),
+            known("kind", true, |k| EffectKind::from_name(k).is_some()),
+            integer("amount", 0.0, 1000.0),
+            known("stat", false, |s| Stat::from_name(s).is_some()),
+            known("status", false, |s| StatusKind::from_name(s).is_some()),
+            integer("turns", 0.0, 99.0),
+        ],
+    },
+    Table {
+        name: "items",
+        columns: &[
+            name(),
+            // Weapons have a table of their own.
+            known("category", true, |c| {
+                ItemCategory::from_name(c).is_some_and(|c| c != ItemCategory::Weapon)
+            }),
+            number("weight", 0.0, 1000.0),
+            integer("value", 0.0, 100_000.0),
+            integer("max_stack", 1.0, 999.0),
+            reference("effect", false, &["effects"], "name"),
+            Column {
+                name: "description",
+                required: false,
+                check: Check::Text,
+            },
+            known("slot", false, |s| EquipSlot::from_name(s).is_some()),
+            integer("defense", 0.0, 999.0),
+            integer("min_physique", 0.0, 99.0),
+            integer("min_technique", 0.0, 99.0),
+            integer("min_mystique", 0.0, 99.0),
+        ],
+    },
+    Table {
+        name: "skills",
+        columns: &[
+            name(),
+            known("resource", true, |r| Cost::from_name(r, 1).is_some()),
+            integer("cost", 0.0, 999.0),
+            number("physique", 0.0, 5.0),
+            number("technique", 0.0, 5.0),
+            number("mystique", 0.0, 5.0),
+            known("target", true, |t| SkillTarget::from_name(t).is_some()),
+            reference("effect", false, &["effects"], "name"),
+            Column {
+                name: "level",
+                required: false,
+                check: Check::Number {
+                    min: 1.0,
+                    max: 100.0,
+                    integer: true,
+                },
+            },
+            Column {
+                name: "price",
+                required: false,
+                check: Check::Number {
+                    min: 0.0,
+                    max: 100_000.0,
+                    integer: true,
+                },
+            },
+            Column {
+                name: "description",
+                required: false,
+                check: Check::Text,
+            },
+        ],
+    },
+];
+
+struct Row {
+    id: i64,
+    values: HashMap<String, Value>,
+}
+
+impl Row {
+    fn get(&self, column: &str) -> &Value {
+        self.values.get(column).unwrap_or(&Value::Null)
+    }
+
+    fn text(&self, column: &str) -> Option<String> {
+        match self.get(column) {
+            Value::String(s) => Some(s.clone()),
+            Value::Integer(i) => Some(i.to_string()),
+            Value::Float(f) => Some(f.to_string()),
+            _ => None,
+        }
+    }
+
+    fn number(&self, column: &str) -> Option<f64> {
+        match self.get(column) {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+}
+
+/// Every row of `table`, or None if there's no such table.
+fn rows(db: &Connection, table: &str) -> Result<Option<(Vec<String>, Vec<Row>)>> {
+    let mut statement = match db.prepare(format!("SELECT rowid AS \"rowid\", * FROM \"{}\"", table))
+    {
+        Ok(statement) => statement,
+        Err(_) => return Ok(None),
+    };
+    let columns: Vec<String> = statement.names().iter().map(|n| n.to_string()).collect();
+    let mut rows = Vec::new();
+    while let State::Row = statement.next()? {
+        let mut values = HashMap::new();
+        for (i, column) in columns.iter().enumerate().skip(1) {
+            values.insert(column.clone(), statement.read::<Value>(i)?);
+        }
+        rows.push(Row {
+            id: statement.read(0)?,
+            values,
+        });
+    }
+    Ok(Some((columns, rows)))
+}
+
+fn check_value(check: &Check, row: &Row, column: &str, refs: &HashSet<String>) -> Option<String> {
+    match check {
+        Check::Name | Check::Label => match row.text(column) {
+            Some(s) if s.trim().is_empty() => Some(format!("{} is blank", column)),
+            Some(_) => None,
+            None => Some(format!("{} isn't text", column)),
+        },
+        Check::Text => match row.text(column) {
+            Some(_) => None,
+            None => Some(format!("{} isn't text", column)),
+        },
+        Check::Known(known) => match row.text(column) {
+            Some(s) if known(&s) => None,
+            s => Some(format!(
+                "{} is {}, which the game doesn't know",
+                column,
+                s.unwrap_or_default()
+            )),
+        },
+        Check::Number { min, max, integer } => {
+            let n = match row.number(column) {
+                Some(n) => n,
+                None => return Some(format!("{} isn't a number", column)),
+            };
+            if *integer && n.fract() != 0.0 {
+                Some(format!("{} is {}, which isn't a whole number", column, n))
+            } else if n < *min || n > *max {
+                Some(format!(
+                    "{} is {}, outside of {} to {}",
+                    column, n, min, max
+                ))
+            } else {
+                None
+            }
+        }
+        Check::Reference { tables, .. } => {
+            let target = row.text(column).unwrap_or_default();
+            if refs.contains(&target) {
+                None
+            } else {
+                Some(format!(
+                    "{} {} isn't in the {} table",
+                    column,
+                    target,
+                    tables.join(" or ")
+                ))
+            }
+        }
+    }
+}
+
+/// Checks every content table and returns everything wrong with it.
+///
+/// Problems are reported per row rather than stopping at the first, so a
+/// content author can fix them all in one go. A database that hasn't been
+/// migrated is checked as it is, with the old schema reported first.
+pub fn validate_content(db: &Connection) -> Result<Vec<Diagnostic>> {
+    let mut loaded = HashMap::new();
+    for table in TABLES.iter() {
+        loaded.insert(table.name, rows(db, table.name)?);
+    }
+    let mut diagnostics = Vec::new();
+    let version = current_version(db)?;
+    if version != latest_version() {
+        diagnostics.push(Diagnostic {
+            table: "schema_version".into(),
+            row: None,
+            name: None,
+            message: format!(
+                "the database is at schema version {}, but the game expects {}",
+                version,
+                latest_version()
+            ),
+        });
+    }
+    for table in TABLES.iter() {
+        let (columns, rows) = match &loaded[table.name] {
+            Some(t) => t,
+            None => {
+                diagnostics.push(Diagnostic {
+                    table: table.name.into(),
+                    row: None,
+                    name: None,
+                    message: "the table is missing".into(),
+                });
+                continue;
+            }
+        };
+        let mut present = Vec::new();
+        for column in table.columns.iter() {
+            if columns.iter().any(|c| c == column.name) {
+                present.push(column);
+            } else {
+                diagnostics.push(Diagnostic {
+                    table: table.name.into(),
+                    row: None,
+                    name: None,
+                    message: format!("the {} column is missing", column.name),
+                });
+            }
+        }
+
+        let mut seen: HashMap<String, i64> = HashMap::new();
+        for row in rows.iter() {
+            let name = row.text("name").filter(|n| !n.trim().is_empty());
+            let mut report = |message: String| {
+                diagnostics.push(Diagnostic {
+                    table: table.name.into(),
+                    row: Some(row.id),
+                    name: name.clone(),
+                    message,
+                })
+            };
+            for column in present.iter() {
+                if let Value::Null = row.get(column.name) {
+                    if column.required {
+                        report(format!("{} is missing", column.name));
+                    }
+                    continue;
+                }
+                let refs = match column.check {
+                    Check::Reference { tables, column } => tables
+                        .iter()
+                        .flat_map(|table| references(&loaded, table, column))
+                        .collect(),
+                    _ => HashSet::new(),
+                };
+                if let Some(message) = check_value(&column.check, row, column.name, &refs) {
+                    report(message);
+                }
+                if let (Check::Name, Some(n)) = (&column.check, &name) {
+                    match seen.get(n) {
+                        Some(first) => report(format!("{} is also the name of row {}", n, first)),
+                        None => {
+                            seen.insert(n.clone(), row.id);
+                        }
+                    }
+                }
+            }
+            if let (Some(min), Some(max)) = (row.number("min_level"), row.number("max_level")) {
+                if min > max {
+                    report(format!("min_level {} is above max_level {}", min, max));
+                }
+            }
+            if table.name == "effects"
+                && row.text("kind").as_deref() == Some("buff")
+                && row.text("stat").is_none()
+            {
+                report("a buff needs a stat to raise".into());
+            }
+            if table.name == "effects"
+                && row.text("kind").as_deref() == Some("status")
+                && (row.text("status").is_none() || row.number("turns").unwrap_or(0.0) < 1.0)
+            {
+                report("a status effect needs a status and at least one turn".into());
+            }
+            if let Some(list) = row.text("immunities") {
+                for status in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    if StatusKind::from_name(status).is_none() {
+                        report(format!("{} isn't a status to be immune to", status));
+                    }
+                }
+            }
+            if table.name == "items"
+                && row.text("category").as_deref() == Some("armor")
+                && row.text("slot").is_none()
+            {
+                report("armor needs a slot to be worn in".into());
+            }
+            if table.name == "skills" {
+                if row.text("target").as_deref() == Some("self") && row.text("effect").is_none() {
+                    report("a skill used on yourself needs an effect".into());
+                }
+                if row.number("level").is_none() && row.number("price").is_none() {
+                    report("a skill needs a level or a price, or it can't be learned".into());
+                }
+            }
+        }
+    }
+    Ok(diagnostics)
+}
+
+type Loaded = HashMap<&'static str, Option<(Vec<String>, Vec<Row>)>>;
+
+/// The values of `column` in `table`, which references must be one of.
+fn references(loaded: &Loaded, table: &str, column: &str) -> HashSet<String> {
+    match loaded.get(table) {
+        Some(Some((_, rows))) => rows.iter().filter_map(|r| r.text(column)).collect(),
+        _ => HashSet::new(),
+    }
+}
+
+mod test {
+    #[test]
+    fn shipped_content_is_valid() {
+        let db = crate::persistence::test_db();
+        let diagnostics = crate::validate::validate_content(&db).unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn reports_each_problem_by_row() {
+        let db = sqlite::open(":memory:").unwrap();
+        crate::schema::migrate(&db).unwrap();
+        db.execute(
+            "INSERT INTO weapons (name, weight, value, physique, technique, mystique) VALUES ('Sword', NULL, 10, 1, 1, 9);
+             INSERT INTO weapons (name, weight, value, physique, technique, mystique) VALUES ('Axe', 5, 10, 1, 1, 1);
+             INSERT INTO enemies (name, weapon, min_level, max_level) VALUES ('Orc', 'Spork', 3, 2);
+             INSERT INTO loot_tables VALUES ('orc', 'Axe', 0.5);
+             INSERT INTO loot_tables VALUES ('orc', 'Sword', 0.1);
+             INSERT INTO loot_tables VALUES (' ', 'Axe', 0.5);
+             INSERT INTO items (name, category) VALUES ('Rope', 'material');
+             INSERT INTO loot_tables VALUES ('orc', 'Rope', 0.5);
+             INSERT INTO loot_tables VALUES ('orc', 'Spork', 0.5);
+             INSERT INTO effects (name, kind) VALUES ('Zap', 'zap');",
+        )
+        .unwrap();
+        let messages: Vec<String> = crate::validate::validate_content(&db)
+            .unwrap()
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "weapons row 1 (Sword): weight is missing",
+                "weapons row 1 (Sword): mystique is 9, outside of 0 to 5",
+                "enemies row 1 (Orc): weapon Spork isn't in the weapons table",
+                "enemies row 1 (Orc): min_level 3 is above max_level 2",
+                "loot_tables row 3: name is blank",
+                "loot_tables row 5 (orc): item Spork isn't in the weapons or items table",
+                "effects row 1 (Zap): kind is zap, which the game doesn't know",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_columns_and_duplicates() {
+        let db = sqlite::open(":memory:").unwrap();
+        db.execute(
+            "CREATE TABLE weapons (name TEXT, weight NUMERIC, value INTEGER, physique NUMERIC, technique NUMERIC, mystique NUMERIC);
+             INSERT INTO weapons (name, weight, value, physique, technique, mystique) VALUES ('Club', 1, 1, 1, 1, 1);
+             INSERT INTO weapons (name, weight, value, physique, technique, mystique) VALUES ('Club', 2, 2, 1, 1, 1);
+             CREATE TABLE enemies (name TEXT);",
+        )
+        .unwrap();
+        let messages: Vec<String> = crate::validate::validate_content(&db)
+            .unwrap()
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert!(messages.contains(&"weapons row 2 (Club): Club is also the name of row 1".into()));
+        assert!(messages.contains(&"enemies: the physique column is missing".into()));
+        assert!(messages.contains(&"loot_tables: the table is missing".into()));
+    }
+
+    #[test]
+    fn old_databases_are_checked_without_migrating() {
+        use crate::schema::current_version;
+        let path = crate::store::test_dir("validate-old").join("qrpg.db");
+        let _ = std::fs::remove_file(&path);
+        sqlite::open(&path)
+            .unwrap()
+            .execute(
+                "CREATE TABLE weapons (name TEXT, weight NUMERIC, value INTEGER, physique NUMERIC, technique NUMERIC, mystique NUMERIC);
+                 CREATE TABLE enemies (name TEXT);
+                 INSERT INTO enemies VALUES ('Goblin');
+                 INSERT INTO enemies VALUES ('Goblin');",
+            )
+            .unwrap();
+        let db = crate::read_db(&path).unwrap();
+        let messages: Vec<String> = crate::validate::validate_content(&db)
+            .unwrap()
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert!(messages[0].starts_with("schema_version: the database is at schema version 1"));
+        assert!(
+            messages.contains(&"enemies row 2 (Goblin): Goblin is also the name of row 1".into())
+        );
+        assert_eq!(current_version(&db).unwrap(), 1);
+    }
+}